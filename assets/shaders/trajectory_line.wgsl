@@ -0,0 +1,62 @@
+#import bevy_sprite::{
+    mesh2d_functions as mesh_functions,
+    mesh2d_view_bindings::view,
+}
+
+#ifdef TONEMAP_IN_SHADER
+#import bevy_core_pipeline::tonemapping
+#endif
+
+// world units per pixel
+@group(2) @binding(0) var<uniform> scale: f32;
+
+struct Vertex {
+    @builtin(instance_index) instance_index: u32,
+    // on the middle of the line
+    @location(0) position: vec3<f32>,
+    @location(1) color: vec4<f32>,
+    // direction of the segment in world units and the offset to the edge of the line in pixels
+    @location(2) segment: vec4<f32>,
+    // length of the line up to the vertex in world units and the dash length in pixels
+    @location(3) dash: vec2<f32>,
+};
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) color: vec4<f32>,
+    @location(1) dash: vec2<f32>,
+};
+
+@vertex
+fn vertex(vertex: Vertex) -> VertexOutput {
+    var out: VertexOutput;
+    let world_from_local = mesh_functions::get_world_from_local(vertex.instance_index);
+    let length = length(vertex.segment.xy);
+    var normal = vec2<f32>(0.0);
+    if length > 0.0 {
+        normal = vec2<f32>(-vertex.segment.y, vertex.segment.x) / length;
+    }
+    let position = vertex.position + vec3<f32>(normal * vertex.segment.w * scale, 0.0);
+    out.position = mesh_functions::mesh2d_position_local_to_clip(
+        world_from_local,
+        vec4<f32>(position, 1.0),
+    );
+    out.color = vertex.color;
+    out.dash = vertex.dash;
+    return out;
+}
+
+@fragment
+fn fragment(in: VertexOutput) -> @location(0) vec4<f32> {
+    // every other dash length along the line is a gap
+    let dash = in.dash.y;
+    if dash > 0.0 && u32(in.dash.x / (dash * scale)) % 2u == 1u {
+        discard;
+    }
+
+    var output_color = in.color;
+#ifdef TONEMAP_IN_SHADER
+    output_color = tonemapping::tone_mapping(output_color, view.color_grading);
+#endif
+    return output_color;
+}
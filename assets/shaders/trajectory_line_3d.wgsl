@@ -0,0 +1,73 @@
+#import bevy_pbr::{
+    mesh_functions,
+    mesh_view_bindings::view,
+    view_transformations::position_world_to_clip,
+}
+
+#ifdef TONEMAP_IN_SHADER
+#import bevy_core_pipeline::tonemapping
+#endif
+
+// world units per pixel at the point the camera orbits
+@group(2) @binding(0) var<uniform> scale: f32;
+// physical pixels per logical pixel
+@group(2) @binding(1) var<uniform> pixel_ratio: f32;
+
+struct Vertex {
+    @builtin(instance_index) instance_index: u32,
+    // on the middle of the line
+    @location(0) position: vec3<f32>,
+    @location(1) color: vec4<f32>,
+    // direction of the segment in world units and the offset to the edge of the line in pixels
+    @location(2) segment: vec4<f32>,
+    // length of the line up to the vertex in world units and the dash length in pixels
+    @location(3) dash: vec2<f32>,
+};
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) color: vec4<f32>,
+    @location(1) dash: vec2<f32>,
+};
+
+@vertex
+fn vertex(vertex: Vertex) -> VertexOutput {
+    var out: VertexOutput;
+    let world_from_local = mesh_functions::get_world_from_local(vertex.instance_index);
+    let world_position = mesh_functions::mesh_position_local_to_world(
+        world_from_local,
+        vec4<f32>(vertex.position, 1.0),
+    );
+    var clip = position_world_to_clip(world_position.xyz);
+    let clip_direction = view.clip_from_world * world_from_local * vec4<f32>(vertex.segment.xyz, 0.0);
+
+    // direction of the segment on screen, from the derivative of the perspective division
+    let viewport_size = view.viewport.zw;
+    let screen_direction = (clip_direction.xy * clip.w - clip.xy * clip_direction.w) * viewport_size;
+    let length = length(screen_direction);
+    if length > 0.0 {
+        let normal = vec2<f32>(-screen_direction.y, screen_direction.x) / length;
+        let offset = normal * vertex.segment.w * pixel_ratio * 2.0 / viewport_size;
+        clip += vec4<f32>(offset * clip.w, 0.0, 0.0);
+    }
+
+    out.position = clip;
+    out.color = vertex.color;
+    out.dash = vertex.dash;
+    return out;
+}
+
+@fragment
+fn fragment(in: VertexOutput) -> @location(0) vec4<f32> {
+    // every other dash length along the line is a gap
+    let dash = in.dash.y;
+    if dash > 0.0 && u32(in.dash.x / (dash * scale)) % 2u == 1u {
+        discard;
+    }
+
+    var output_color = in.color;
+#ifdef TONEMAP_IN_SHADER
+    output_color = tonemapping::tone_mapping(output_color, view.color_grading);
+#endif
+    return output_color;
+}
@@ -3,19 +3,63 @@ use bevy::{
     prelude::*,
     utils::ConditionalSendFuture,
 };
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
-#[derive(Asset, TypePath, Debug, Deserialize)]
+use crate::sim::{
+    appearance::{Appearance, Glow, Rings},
+    forces::BodyForces,
+    manoeuvre::Manoeuvres,
+    trajectory::TrajectoryStyle,
+};
+
+#[derive(Asset, TypePath, Debug, Serialize, Deserialize)]
 pub struct Body {
     pub initial_pos: Vec2,
     /// in m/s
     pub velocity: Vec2,
+    /// Out-of-plane position, for 3D systems
+    #[serde(default)]
+    pub z: f32,
+    /// Out-of-plane velocity, for 3D systems
+    #[serde(default)]
+    pub velocity_z: f32,
+    /// Tilt of the body's initial position and velocity around the x axis, in degrees
+    #[serde(default)]
+    pub inclination: f32,
     /// in kg
     pub mass: f32,
     pub radius: f32,
     pub color: Color,
     pub name: String,
+    /// Free-form label the body list can group bodies by
+    #[serde(default)]
+    pub tag: Option<String>,
+    #[serde(default)]
+    pub trajectory_style: TrajectoryStyle,
+    /// Path of an image drawn on the body, relative to the assets folder
+    #[serde(default)]
+    pub texture: Option<String>,
+    #[serde(default)]
+    pub glow: Option<Glow>,
+    #[serde(default)]
+    pub rings: Option<Rings>,
+    /// Thrust, drag and radiation pressure acting on or caused by the body
+    #[serde(default)]
+    pub forces: BodyForces,
+    /// Burns planned for the body
+    #[serde(default)]
+    pub manoeuvres: Manoeuvres,
+}
+
+impl Body {
+    pub fn appearance(&self) -> Appearance {
+        Appearance {
+            texture: self.texture.clone(),
+            glow: self.glow.clone(),
+            rings: self.rings.clone(),
+        }
+    }
 }
 
 #[derive(Default)]
@@ -3,14 +3,18 @@ use bevy::{
     prelude::*,
     utils::ConditionalSendFuture,
 };
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
-#[derive(Asset, TypePath, Debug, Deserialize)]
+#[derive(Asset, TypePath, Debug, Serialize, Deserialize)]
 pub struct System {
     pub folder: String,
     pub display_name: String,
     pub gravitational_const: f32,
+    /// Enables the first-order post-Newtonian correction with this speed of light, in the
+    /// system's units
+    #[serde(default)]
+    pub speed_of_light: Option<f32>,
 }
 
 #[derive(Default)]
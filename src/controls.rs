@@ -8,15 +8,92 @@ use bevy::{
 };
 
 use crate::{
-    sim::{self, Follow, Hover, Mass, Name, Radius, SimSnapshot, Trajectory, TrajectoryVisibility},
+    input::{Action, ActionInput, BOOKMARK_COUNT},
+    selection::{Selected, SelectionEvent},
+    sim::{
+        self, frame::CurrentFrame, Follow, Hover, Mass, Name, Radius, Trajectory,
+        TrajectoryVisibility,
+    },
     ui::{self, Inspect},
     utils, AppState,
 };
 
+const CAM_SPEED: f32 = 300.0;
+
+const GAMEPAD_ZOOM_SPEED: f32 = 2.0;
+
+const TRANSITION_DURATION: f32 = 0.6;
+
 #[derive(Resource, Default)]
 struct ControlState {
     cam_origin: Vec2,
     frame_delta: Vec2,
+    select_box_start: Option<Vec2>,
+    followed: Vec<Entity>,
+    transition: Option<CameraTransition>,
+    /// Zoom of the [`FakeCam`] that the camera takes on in the next update
+    zoom: Option<PendingZoom>,
+}
+
+#[derive(Clone, Copy)]
+enum PendingZoom {
+    /// Keeps the centre of the view in place
+    Centre,
+    /// Keeps the world position under the cursor in place
+    Cursor { cursor: Vec2, world: Vec2 },
+}
+
+/// An animation of the camera towards a new position and zoom
+struct CameraTransition {
+    from_centre: Vec2,
+    from_scale: f32,
+    /// Final offset of the camera from the followed bodies, or the final centre if none are
+    /// followed
+    to_offset: Vec2,
+    to_scale: f32,
+    elapsed: f32,
+}
+
+impl CameraTransition {
+    /// `anchor` is the centre of mass of the followed bodies, which the camera catches up with
+    fn current(&self, anchor: Vec2) -> (Vec2, f32) {
+        let t = (self.elapsed / TRANSITION_DURATION).clamp(0.0, 1.0);
+        let eased = t * t * (3.0 - 2.0 * t);
+
+        let centre = self.from_centre.lerp(anchor + self.to_offset, eased);
+        let scale =
+            (self.from_scale.ln() + (self.to_scale.ln() - self.from_scale.ln()) * eased).exp();
+        (centre, scale)
+    }
+
+    fn is_finished(&self) -> bool {
+        self.elapsed >= TRANSITION_DURATION
+    }
+}
+
+/// A saved camera view
+#[derive(Clone, Debug)]
+pub struct CameraBookmark {
+    pub name: String,
+    /// Offset from the followed bodies, or the world space centre if none are followed
+    pub centre: Vec2,
+    pub scale: f32,
+    pub follow: Vec<Entity>,
+}
+
+#[derive(Resource, Default)]
+pub struct CameraBookmarks(pub Vec<CameraBookmark>);
+
+#[derive(Event, Clone, Debug)]
+pub enum CameraEvent {
+    /// Saves the current view as a new bookmark, or overwrites the bookmark at the index
+    SaveBookmark {
+        name: String,
+        index: Option<usize>,
+    },
+    RecallBookmark(usize),
+    /// Frames all bodies
+    FitAll,
 }
 
 #[derive(States, Default, Clone, PartialEq, Eq, Hash, Debug)]
@@ -87,42 +164,201 @@ fn spawn_fake_body(
         .insert((radius, name, mass, PreSpawn));
 }
 
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn cam_controller_bookmarks(
+    mut camera_evr: EventReader<CameraEvent>,
+    mut bookmarks: ResMut<CameraBookmarks>,
+    mut control_state: ResMut<ControlState>,
+    q_camera: Query<(&Camera, &Transform, &OrthographicProjection), With<SimCamera>>,
+    q_bodies: Query<(Entity, &Transform, &Radius), (With<Trajectory>, Without<SimCamera>)>,
+    q_followed: Query<Entity, With<Follow>>,
+    mut cmds: Commands,
+) {
+    let (cam, cam_transform, projection) = q_camera.single();
+    let centre = cam_transform.translation.xy();
+
+    for ev in camera_evr.read() {
+        let (follow, to_offset, to_scale) = match ev {
+            CameraEvent::SaveBookmark { name, index } => {
+                let bookmark = CameraBookmark {
+                    name: name.clone(),
+                    centre: centre - control_state.cam_origin,
+                    scale: projection.scale,
+                    follow: q_followed.iter().collect(),
+                };
+                match index.and_then(|index| bookmarks.0.get_mut(index)) {
+                    Some(existing) => *existing = bookmark,
+                    None => bookmarks.0.push(bookmark),
+                }
+                continue;
+            }
+            CameraEvent::RecallBookmark(index) => {
+                let Some(bookmark) = bookmarks.0.get(*index) else {
+                    continue;
+                };
+                let follow = bookmark
+                    .follow
+                    .iter()
+                    .copied()
+                    .filter(|entity| q_bodies.contains(*entity))
+                    .collect::<Vec<_>>();
+                (follow, bookmark.centre, bookmark.scale)
+            }
+            CameraEvent::FitAll => {
+                let Some(bounds) = q_bodies
+                    .iter()
+                    .map(|(_, transform, Radius(radius))| {
+                        Rect::from_center_half_size(
+                            transform.translation.xy(),
+                            Vec2::splat(*radius),
+                        )
+                    })
+                    .reduce(|a, b| a.union(b))
+                else {
+                    continue;
+                };
+                let viewport_size = cam.logical_viewport_size().unwrap_or(Vec2::ONE);
+                let scale = (bounds.size() / viewport_size).max_element() * 1.1;
+
+                (Vec::new(), bounds.center(), scale.max(f32::MIN_POSITIVE))
+            }
+        };
+
+        for entity in q_followed.iter() {
+            cmds.entity(entity).remove::<Follow>();
+        }
+        for entity in &follow {
+            cmds.entity(*entity).insert(Follow);
+        }
+
+        control_state.transition = Some(CameraTransition {
+            from_centre: centre,
+            from_scale: projection.scale,
+            to_offset,
+            to_scale,
+            elapsed: 0.0,
+        });
+    }
+}
+
+fn cam_controller_bookmark_keys(
+    input: ActionInput,
+    bookmarks: Res<CameraBookmarks>,
+    mut camera_evw: EventWriter<CameraEvent>,
+) {
+    if input.just_pressed(Action::FitAll) {
+        camera_evw.send(CameraEvent::FitAll);
+    }
+
+    for index in 0..BOOKMARK_COUNT {
+        if !input.just_pressed(Action::Bookmark(index)) {
+            continue;
+        }
+        if input.pressed(Action::SaveBookmark) {
+            let name = bookmarks
+                .0
+                .get(index)
+                .map_or(format!("View {}", index + 1), |bookmark| {
+                    bookmark.name.clone()
+                });
+            camera_evw.send(CameraEvent::SaveBookmark {
+                name,
+                index: Some(index),
+            });
+        } else {
+            camera_evw.send(CameraEvent::RecallBookmark(index));
+        }
+    }
+}
+
 #[allow(clippy::too_many_arguments, clippy::type_complexity)]
 fn cam_controller_core(
-    kb: Res<ButtonInput<KeyCode>>,
-    mut q_camera: Query<(&Camera, &GlobalTransform, &mut Transform), With<SimCamera>>,
-    q_focused: Query<(Entity, &Transform), (With<sim::Follow>, Without<SimCamera>)>,
-    q_bodies: Query<(Entity, &Trajectory, &Radius)>,
+    input: ActionInput,
+    mut q_camera: Query<
+        (
+            &Camera,
+            &GlobalTransform,
+            &mut Transform,
+            &mut OrthographicProjection,
+        ),
+        With<SimCamera>,
+    >,
+    q_focused: Query<(Entity, &Transform, &Mass), (With<sim::Follow>, Without<SimCamera>)>,
+    q_bodies: Query<
+        (Entity, &Transform, &Radius, Has<Selected>),
+        (With<Trajectory>, Without<SimCamera>),
+    >,
     q_windows: Query<&Window, With<PrimaryWindow>>,
     q_already_followed: Query<Entity, With<Follow>>,
     q_already_inspected: Query<Entity, With<Inspect>>,
-    mouse: Res<ButtonInput<MouseButton>>,
+    q_already_selected: Query<Entity, With<Selected>>,
     mut control_state: ResMut<ControlState>,
     mut next_ctrl_mode: ResMut<NextState<ControlMode>>,
     one_shots: Res<OneShotSystems>,
+    time: Res<Time>,
     mut cmds: Commands,
 ) {
-    let focused = q_focused.get_single().ok();
-    let (cam, cam_global_transform, mut cam_transform) = q_camera.single_mut();
+    let (cam, cam_global_transform, mut cam_transform, mut projection) = q_camera.single_mut();
 
     control_state.frame_delta = Vec2::ZERO;
 
-    if let Some((e, transform)) = focused {
-        cam_transform.translation -= control_state.cam_origin.extend(0.0);
-        control_state.cam_origin = transform.translation.xy();
-        if kb.pressed(KeyCode::Escape) {
-            cmds.entity(e).remove::<sim::Follow>();
-        }
+    // the camera is placed relative to the followed bodies, at `cam_origin`
+    let centre = cam_transform.translation.xy();
+    cam_transform.translation -= control_state.cam_origin.extend(0.0);
+
+    let mut followed = q_focused.iter().map(|(e, ..)| e).collect::<Vec<_>>();
+    followed.sort();
+
+    let anchor = if followed.is_empty() {
+        Vec2::ZERO
     } else {
-        control_state.cam_origin = Vec2::ZERO;
+        sim::centre_of_mass(
+            q_focused
+                .iter()
+                .map(|(_, transform, Mass(mass))| (transform.translation.xy(), *mass)),
+        )
+    };
+
+    if followed != control_state.followed {
+        if control_state.transition.is_none() && !followed.is_empty() {
+            // glide over to the newly followed bodies instead of jumping
+            control_state.transition = Some(CameraTransition {
+                from_centre: centre,
+                from_scale: projection.scale,
+                to_offset: Vec2::ZERO,
+                to_scale: projection.scale,
+                elapsed: 0.0,
+            });
+        } else if followed.is_empty() {
+            // keep the camera where it is
+            cam_transform.translation = centre.extend(cam_transform.translation.z);
+        }
+        control_state.followed = followed;
+    }
+
+    if let Some(transition) = &mut control_state.transition {
+        transition.elapsed += time.delta_seconds();
+        let (centre, scale) = transition.current(anchor);
+
+        cam_transform.translation = (centre - anchor).extend(cam_transform.translation.z);
+        projection.scale = scale;
+
+        if transition.is_finished() {
+            control_state.transition = None;
+        }
+    }
+    control_state.cam_origin = anchor;
+
+    if !control_state.followed.is_empty() && input.pressed(Action::Cancel) {
+        for entity_id in q_already_followed.iter() {
+            cmds.entity(entity_id).remove::<sim::Follow>();
+        }
     }
 
     if let Some(cursor_pos) = q_windows.single().cursor_position() {
-        for (entity_id, trajectory, Radius(radius)) in q_bodies.iter() {
-            let SimSnapshot {
-                velocity: _,
-                position,
-            } = trajectory.front().unwrap();
+        for (entity_id, transform, Radius(radius), is_selected) in q_bodies.iter() {
+            // bodies are placed relative to the reference frame, just like the cursor
+            let position = transform.translation.xy();
             // convert to world space
             let cursor_pos = cam
                 .viewport_to_world_2d(cam_global_transform, cursor_pos)
@@ -134,7 +370,7 @@ fn cam_controller_core(
                 && cursor_pos.y < position.y + radius
             {
                 cmds.entity(entity_id).try_insert(Hover);
-                if mouse.just_pressed(MouseButton::Middle) {
+                if input.just_pressed(Action::Follow) {
                     cmds.entity(entity_id).insert(Follow);
 
                     for entity_id in q_already_followed.iter() {
@@ -142,21 +378,33 @@ fn cam_controller_core(
                     }
                 }
 
-                if mouse.just_pressed(MouseButton::Left) || mouse.just_pressed(MouseButton::Middle)
-                {
+                if input.just_pressed(Action::Select) || input.just_pressed(Action::Follow) {
                     cmds.entity(entity_id).insert(Inspect);
 
                     for entity_id in q_already_inspected.iter() {
                         cmds.entity(entity_id).remove::<Inspect>();
                     }
                 }
+
+                if input.just_pressed(Action::Select) {
+                    if !input.pressed(Action::AddToSelection) {
+                        for entity_id in q_already_selected.iter() {
+                            cmds.entity(entity_id).remove::<Selected>();
+                        }
+                        cmds.entity(entity_id).insert(Selected);
+                    } else if is_selected {
+                        cmds.entity(entity_id).remove::<Selected>();
+                    } else {
+                        cmds.entity(entity_id).insert(Selected);
+                    }
+                }
             } else {
                 cmds.entity(entity_id).remove::<Hover>();
             }
         }
     }
 
-    if kb.pressed(KeyCode::ControlLeft) && kb.just_pressed(KeyCode::KeyN) {
+    if input.just_pressed(Action::SpawnBody) {
         next_ctrl_mode.set(ControlMode::Spawn);
         cmds.run_system(one_shots.0["spawn_fake_body"]);
     }
@@ -164,44 +412,33 @@ fn cam_controller_core(
 
 #[allow(clippy::too_many_arguments, clippy::type_complexity)]
 fn cam_controller_normal(
-    mut q_camera: Query<
-        (&mut OrthographicProjection, &Camera, &GlobalTransform),
+    q_camera: Query<
+        (&OrthographicProjection, &Camera, &GlobalTransform),
         (With<Camera2d>, With<SimCamera>),
     >,
-    mut q_fake_camera: Query<
-        (&mut OrthographicProjection, &Camera),
-        (With<FakeCam>, Without<SimCamera>),
-    >,
+    mut q_fake_camera: Query<&mut OrthographicProjection, (With<FakeCam>, Without<SimCamera>)>,
     q_windows: Query<&Window, With<PrimaryWindow>>,
     mut wheel: EventReader<MouseWheel>,
-    mouse: Res<ButtonInput<MouseButton>>,
+    input: ActionInput,
     mut cursor_moved: EventReader<CursorMoved>,
     mut control_state: ResMut<ControlState>,
-    mut zoom_diff: Local<Option<Vec2>>,
     time: Res<Time>,
 ) {
     let dt = time.delta_seconds();
 
-    let (mut projection, cam, global_transform) = q_camera.single_mut();
-    let (mut fake_projection, fake_cam) = q_fake_camera.single_mut();
+    let (projection, cam, global_transform) = q_camera.single();
+    let mut fake_projection = q_fake_camera.single_mut();
 
     let mut log_scale = projection.scale.ln();
 
-    if let Some(prev_cursor_pos) = *zoom_diff {
-        let current_cursor_pos = fake_cam
-            .viewport_to_world_2d(
-                global_transform,
-                q_windows.single().cursor_position().unwrap(),
-            )
-            .unwrap();
-
-        projection.scale = fake_projection.scale;
-        control_state.frame_delta += prev_cursor_pos - current_cursor_pos;
-
-        *zoom_diff = None;
-    }
-
     for ev in wheel.read() {
+        let Some(cursor) = q_windows.single().cursor_position() else {
+            continue;
+        };
+        let Some(world) = cam.viewport_to_world_2d(global_transform, cursor) else {
+            continue;
+        };
+
         log_scale -= ev.y
             * dt
             * match ev.unit {
@@ -209,16 +446,11 @@ fn cam_controller_normal(
                 MouseScrollUnit::Pixel => 7.0,
             };
         fake_projection.scale = log_scale.exp();
-        *zoom_diff = Some(
-            cam.viewport_to_world_2d(
-                global_transform,
-                q_windows.single().cursor_position().unwrap(),
-            )
-            .unwrap(),
-        );
+        control_state.zoom = Some(PendingZoom::Cursor { cursor, world });
     }
 
-    if mouse.pressed(MouseButton::Left) {
+    // dragging while adding to the selection draws a selection box instead of panning
+    if input.pressed(Action::DragPan) && !input.pressed(Action::AddToSelection) {
         for ev in cursor_moved.read() {
             if let Some(delta) = ev.delta {
                 control_state.frame_delta += Vec2::new(-delta.x, delta.y) * log_scale.exp();
@@ -229,8 +461,7 @@ fn cam_controller_normal(
 
 #[allow(clippy::too_many_arguments)]
 fn cam_controller_spawn(
-    kb: Res<ButtonInput<KeyCode>>,
-    mouse: Res<ButtonInput<MouseButton>>,
+    input: ActionInput,
     mut wheel: EventReader<MouseWheel>,
     q_windows: Query<&Window, With<PrimaryWindow>>,
     mut q_pre_spawn: Query<(Entity, &mut Transform, &mut Radius), With<PreSpawn>>,
@@ -238,11 +469,13 @@ fn cam_controller_spawn(
     mut next_ctrl_mode: ResMut<NextState<ControlMode>>,
     mut control_state: ResMut<ControlState>,
     mut clear_traj_evw: EventWriter<sim::ClearTrajectories>,
-    q_focused: Query<&sim::Trajectory, With<sim::Follow>>,
+    q_focused: Query<(&sim::Trajectory, &Mass), With<sim::Follow>>,
+    current_frame: Res<CurrentFrame>,
     mut gizmos: Gizmos,
     mut cmds: Commands,
 ) {
     let (cam, cam_global_transform, _projection) = q_camera.single_mut();
+    let frame = current_frame.0;
     control_state.frame_delta = Vec2::ZERO;
 
     let mouse_position = cam
@@ -260,15 +493,21 @@ fn cam_controller_spawn(
 
     let (entity, mut transform, mut radius) = pre_spawn.unwrap();
 
-    if mouse.just_released(MouseButton::Left) {
+    if input.just_released(Action::Select) {
+        let position = transform.translation.xy();
+        let followed_velocity = sim::centre_of_mass(q_focused.iter().map(|(traj, Mass(mass))| {
+            let current = traj.front().unwrap();
+            (
+                frame.vel_in_frame(current.position, current.velocity),
+                *mass,
+            )
+        }));
+
+        // the body is placed in the reference frame but simulated in the inertial one
         cmds.entity(entity).remove::<PreSpawn>().insert((
             sim::Trajectory::new(
-                transform.translation.xy(),
-                transform.translation.xy() - mouse_position
-                    + q_focused
-                        .get_single()
-                        .map(|q| q.front().unwrap().velocity)
-                        .unwrap_or(Vec2::ZERO),
+                frame.pos_in_inertial(position),
+                frame.vel_in_inertial(position, position - mouse_position + followed_velocity),
             ),
             TrajectoryVisibility(true),
         ));
@@ -278,7 +517,7 @@ fn cam_controller_spawn(
         return;
     }
 
-    if mouse.pressed(MouseButton::Left) {
+    if input.pressed(Action::Select) {
         let transform_2d = transform.translation.xy();
         gizmos.arrow_2d(
             transform_2d,
@@ -293,39 +532,197 @@ fn cam_controller_spawn(
         radius.0 += ev.y;
     }
 
-    if kb.pressed(KeyCode::Escape) {
+    if input.pressed(Action::Cancel) {
         next_ctrl_mode.set(ControlMode::Normal);
         cmds.entity(entity).despawn();
     }
 }
 
+#[allow(clippy::too_many_arguments)]
+fn cam_controller_select(
+    input: ActionInput,
+    q_windows: Query<&Window, With<PrimaryWindow>>,
+    q_camera: Query<(&Camera, &GlobalTransform), With<SimCamera>>,
+    q_bodies: Query<(Entity, &Transform), With<Trajectory>>,
+    q_hovered: Query<(), With<Hover>>,
+    mut control_state: ResMut<ControlState>,
+    mut selection_evw: EventWriter<SelectionEvent>,
+    mut gizmos: Gizmos,
+    mut cmds: Commands,
+) {
+    let (cam, cam_global_transform) = q_camera.single();
+
+    if input.just_pressed(Action::DeleteSelection) {
+        selection_evw.send(SelectionEvent::Delete);
+    }
+
+    let Some(mouse_position) = q_windows
+        .single()
+        .cursor_position()
+        .and_then(|pos| cam.viewport_to_world_2d(cam_global_transform, pos))
+    else {
+        return;
+    };
+
+    // clicking a body is handled by `cam_controller_core`
+    if input.just_pressed(Action::Select)
+        && input.pressed(Action::AddToSelection)
+        && q_hovered.is_empty()
+    {
+        control_state.select_box_start = Some(mouse_position);
+    }
+
+    let Some(start) = control_state.select_box_start else {
+        return;
+    };
+
+    let select_box = Rect::from_corners(start, mouse_position);
+
+    if input.just_released(Action::Select) {
+        for (entity_id, transform) in q_bodies.iter() {
+            if select_box.contains(transform.translation.xy()) {
+                cmds.entity(entity_id).insert(Selected);
+            }
+        }
+        control_state.select_box_start = None;
+    } else if input.pressed(Action::Select) {
+        gizmos.rect_2d(select_box.center(), 0.0, select_box.size(), Color::WHITE);
+    } else {
+        control_state.select_box_start = None;
+    }
+}
+
 fn cam_controller_wasd(
     q_projection: Query<&OrthographicProjection, (With<Camera2d>, With<SimCamera>)>,
     mut control_state: ResMut<ControlState>,
     time: Res<Time>,
-    kb: Res<ButtonInput<KeyCode>>,
+    input: ActionInput,
 ) {
     let projection = q_projection.single();
 
     let dt = time.delta_seconds();
 
-    let cam_speed: f32 = 300.0 * projection.scale;
+    let cam_speed: f32 = CAM_SPEED * projection.scale;
     let dist = cam_speed * dt;
 
-    if kb.pressed(KeyCode::KeyW) {
+    if input.pressed(Action::PanUp) {
         control_state.frame_delta.y += dist;
     }
-    if kb.pressed(KeyCode::KeyA) {
+    if input.pressed(Action::PanLeft) {
         control_state.frame_delta.x -= dist;
     }
-    if kb.pressed(KeyCode::KeyS) {
+    if input.pressed(Action::PanDown) {
         control_state.frame_delta.y -= dist;
     }
-    if kb.pressed(KeyCode::KeyD) {
+    if input.pressed(Action::PanRight) {
         control_state.frame_delta.x += dist;
     }
 }
 
+fn cam_controller_gamepad(
+    q_projection: Query<&OrthographicProjection, (With<Camera2d>, With<SimCamera>)>,
+    mut q_fake_projection: Query<&mut OrthographicProjection, (With<FakeCam>, Without<SimCamera>)>,
+    mut control_state: ResMut<ControlState>,
+    time: Res<Time>,
+    gamepads: Res<Gamepads>,
+    axes: Res<Axis<GamepadAxis>>,
+    button_axes: Res<Axis<GamepadButton>>,
+) {
+    let projection = q_projection.single();
+    let mut fake_projection = q_fake_projection.single_mut();
+    let dt = time.delta_seconds();
+
+    for gamepad in gamepads.iter() {
+        let axis = |axis_type| {
+            axes.get(GamepadAxis::new(gamepad, axis_type))
+                .unwrap_or(0.0)
+        };
+        let trigger = |button_type| {
+            button_axes
+                .get(GamepadButton::new(gamepad, button_type))
+                .unwrap_or(0.0)
+        };
+
+        let stick = Vec2::new(
+            axis(GamepadAxisType::LeftStickX),
+            axis(GamepadAxisType::LeftStickY),
+        );
+        control_state.frame_delta += stick * CAM_SPEED * projection.scale * dt;
+
+        let zoom =
+            trigger(GamepadButtonType::LeftTrigger2) - trigger(GamepadButtonType::RightTrigger2);
+        if zoom != 0.0 {
+            // adds to a zoom with the mouse wheel in the same update
+            if control_state.zoom.is_none() {
+                fake_projection.scale = projection.scale;
+            }
+            fake_projection.scale *= (zoom * GAMEPAD_ZOOM_SPEED * dt).exp();
+            control_state.zoom.get_or_insert(PendingZoom::Centre);
+        }
+    }
+}
+
+/// Gives the camera the scale of the [`FakeCam`] after a zoom in the last update
+#[allow(clippy::type_complexity)]
+fn cam_controller_zoom(
+    mut q_camera: Query<
+        (&mut OrthographicProjection, &GlobalTransform),
+        (With<Camera2d>, With<SimCamera>),
+    >,
+    q_fake_camera: Query<(&OrthographicProjection, &Camera), (With<FakeCam>, Without<SimCamera>)>,
+    mut control_state: ResMut<ControlState>,
+) {
+    let Some(zoom) = control_state.zoom.take() else {
+        return;
+    };
+    let (mut projection, global_transform) = q_camera.single_mut();
+    let (fake_projection, fake_cam) = q_fake_camera.single();
+
+    projection.scale = fake_projection.scale;
+    if let PendingZoom::Cursor { cursor, world } = zoom {
+        if let Some(zoomed) = fake_cam.viewport_to_world_2d(global_transform, cursor) {
+            control_state.frame_delta += world - zoomed;
+        }
+    }
+}
+
+/// Moves [`Follow`] to the next or previous body, in alphabetical order
+pub(crate) fn cam_controller_cycle_follow(
+    input: ActionInput,
+    q_bodies: Query<(Entity, &Name), With<Trajectory>>,
+    q_followed: Query<Entity, With<Follow>>,
+    mut cmds: Commands,
+) {
+    let step = match (
+        input.just_pressed(Action::FollowNext),
+        input.just_pressed(Action::FollowPrevious),
+    ) {
+        (true, false) => 1,
+        (false, true) => -1,
+        _ => return,
+    };
+
+    let mut bodies = q_bodies.iter().collect::<Vec<_>>();
+    if bodies.is_empty() {
+        return;
+    }
+    bodies.sort_by(|(_, a), (_, b)| a.0.cmp(&b.0));
+
+    let next = match bodies
+        .iter()
+        .position(|(entity, _)| q_followed.contains(*entity))
+    {
+        Some(current) => (current as isize + step).rem_euclid(bodies.len() as isize) as usize,
+        None if step > 0 => 0,
+        None => bodies.len() - 1,
+    };
+
+    for entity in q_followed.iter() {
+        cmds.entity(entity).remove::<Follow>();
+    }
+    cmds.entity(bodies[next].0).insert(Follow);
+}
+
 fn cam_controller_apply(
     mut cam_transform: Query<&mut Transform, (With<Camera2d>, With<SimCamera>)>,
     control_state: Res<ControlState>,
@@ -354,6 +751,8 @@ impl Plugin for ControlsPlugin {
 
         app.insert_resource(ClearColor(Color::BLACK))
             .insert_resource(ControlState::default())
+            .init_resource::<CameraBookmarks>()
+            .add_event::<CameraEvent>()
             .insert_resource(one_shots)
             .insert_state(ControlMode::Normal)
             .configure_sets(
@@ -368,11 +767,17 @@ impl Plugin for ControlsPlugin {
             .add_systems(
                 PostUpdate,
                 (
+                    cam_controller_bookmarks,
                     cam_controller_core,
                     (
-                        cam_controller_normal.run_if(in_state(ControlMode::Normal)),
+                        cam_controller_zoom,
+                        (cam_controller_normal, cam_controller_select)
+                            .run_if(in_state(ControlMode::Normal)),
                         cam_controller_spawn.run_if(in_state(ControlMode::Spawn)),
                         cam_controller_wasd,
+                        cam_controller_gamepad,
+                        cam_controller_cycle_follow,
+                        cam_controller_bookmark_keys,
                     )
                         .chain()
                         .run_if(not(ui::ui_is_active)),
@@ -383,3 +788,139 @@ impl Plugin for ControlsPlugin {
             );
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::{
+        input::{
+            gamepad::{
+                GamepadAxisChangedEvent, GamepadConnection, GamepadConnectionEvent, GamepadEvent,
+                GamepadInfo,
+            },
+            InputPlugin,
+        },
+        time::TimeUpdateStrategy,
+    };
+
+    use super::*;
+
+    const GAMEPAD: Gamepad = Gamepad { id: 0 };
+
+    fn app() -> App {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, InputPlugin))
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+                100,
+            )))
+            .init_resource::<ControlState>()
+            .add_systems(
+                Update,
+                (
+                    |mut control_state: ResMut<ControlState>| {
+                        control_state.frame_delta = Vec2::ZERO;
+                    },
+                    cam_controller_zoom,
+                    cam_controller_gamepad,
+                    cam_controller_apply,
+                )
+                    .chain(),
+            );
+        app.world_mut()
+            .spawn((Camera2dBundle::default(), SimCamera));
+        app.world_mut().spawn((
+            Camera::default(),
+            OrthographicProjection::default(),
+            FakeCam,
+        ));
+
+        app.world_mut()
+            .send_event(GamepadEvent::Connection(GamepadConnectionEvent::new(
+                GAMEPAD,
+                GamepadConnection::Connected(GamepadInfo {
+                    name: "Test gamepad".to_string(),
+                }),
+            )));
+        app.update();
+        app
+    }
+
+    fn send(app: &mut App, event: GamepadEvent) {
+        app.world_mut().send_event(event);
+        app.update();
+    }
+
+    /// Sets how far a trigger is pressed, which the gamepad backend writes straight to the
+    /// [`Axis`] instead of going through events
+    fn set_trigger(app: &mut App, button_type: GamepadButtonType, value: f32) {
+        app.world_mut()
+            .resource_mut::<Axis<GamepadButton>>()
+            .set(GamepadButton::new(GAMEPAD, button_type), value);
+        app.update();
+    }
+
+    fn camera<T: Component + Clone>(app: &mut App) -> T {
+        app.world_mut()
+            .query_filtered::<&T, With<SimCamera>>()
+            .single(app.world())
+            .clone()
+    }
+
+    fn fake_scale(app: &mut App) -> f32 {
+        app.world_mut()
+            .query_filtered::<&OrthographicProjection, With<FakeCam>>()
+            .single(app.world())
+            .scale
+    }
+
+    #[test]
+    fn left_stick_pans() {
+        let mut app = app();
+
+        send(
+            &mut app,
+            GamepadEvent::Axis(GamepadAxisChangedEvent::new(
+                GAMEPAD,
+                GamepadAxisType::LeftStickX,
+                1.0,
+            )),
+        );
+        app.update();
+        let panned = camera::<Transform>(&mut app).translation;
+        assert!(panned.x > 0.0);
+        assert_eq!(panned.y, 0.0);
+
+        send(
+            &mut app,
+            GamepadEvent::Axis(GamepadAxisChangedEvent::new(
+                GAMEPAD,
+                GamepadAxisType::LeftStickX,
+                0.0,
+            )),
+        );
+        let released = camera::<Transform>(&mut app).translation;
+        app.update();
+        assert_eq!(camera::<Transform>(&mut app).translation, released);
+    }
+
+    #[test]
+    fn triggers_zoom_through_fake_camera() {
+        let mut app = app();
+
+        set_trigger(&mut app, GamepadButtonType::RightTrigger2, 1.0);
+        // the camera only takes on the zoom of the fake camera in the next update
+        assert_eq!(camera::<OrthographicProjection>(&mut app).scale, 1.0);
+        let zoomed_in = fake_scale(&mut app);
+        assert!(zoomed_in < 1.0);
+
+        set_trigger(&mut app, GamepadButtonType::RightTrigger2, 0.0);
+        assert_eq!(camera::<OrthographicProjection>(&mut app).scale, zoomed_in);
+        // zooming around the centre of the view doesn't move the camera
+        assert_eq!(camera::<Transform>(&mut app).translation, Vec3::ZERO);
+
+        set_trigger(&mut app, GamepadButtonType::LeftTrigger2, 1.0);
+        app.update();
+        assert!(camera::<OrthographicProjection>(&mut app).scale > zoomed_in);
+    }
+}
@@ -0,0 +1,525 @@
+use std::{collections::BTreeMap, hash::Hash};
+
+use bevy::{ecs::system::SystemParam, prelude::*};
+use serde::{Deserialize, Serialize};
+
+/// Everything that can be triggered with a key or mouse button
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Serialize, Deserialize)]
+pub enum Action {
+    PanUp,
+    PanLeft,
+    PanDown,
+    PanRight,
+    /// Pans the camera while held and the mouse moves
+    DragPan,
+    /// Selects and inspects bodies, places new bodies and ruler points
+    Select,
+    Follow,
+    FollowNext,
+    FollowPrevious,
+    SpawnBody,
+    /// Cancels spawning a body, stops following and clears the ruler
+    Cancel,
+    DeleteSelection,
+    FitAll,
+    ToggleRuler,
+    PlayPause,
+    Step,
+    /// Held to add clicked bodies to the selection and to box select instead of panning
+    AddToSelection,
+    /// Held while pressing a bookmark key to save the current view there
+    SaveBookmark,
+    /// Held while dragging in the 3D view to move the camera instead of rotating it
+    Pan3d,
+    /// Recalls the camera bookmark of the index
+    Bookmark(usize),
+}
+
+/// Number of camera bookmarks that have a binding
+pub const BOOKMARK_COUNT: usize = 9;
+
+const BASE_ACTIONS: &[Action] = &[
+    Action::PanUp,
+    Action::PanLeft,
+    Action::PanDown,
+    Action::PanRight,
+    Action::DragPan,
+    Action::Select,
+    Action::Follow,
+    Action::FollowNext,
+    Action::FollowPrevious,
+    Action::SpawnBody,
+    Action::Cancel,
+    Action::DeleteSelection,
+    Action::FitAll,
+    Action::ToggleRuler,
+    Action::PlayPause,
+    Action::Step,
+    Action::AddToSelection,
+    Action::SaveBookmark,
+    Action::Pan3d,
+];
+
+impl Action {
+    pub const ALL: [Action; BASE_ACTIONS.len() + BOOKMARK_COUNT] = {
+        let mut all = [Action::PanUp; BASE_ACTIONS.len() + BOOKMARK_COUNT];
+        let mut index = 0;
+        while index < all.len() {
+            all[index] = if index < BASE_ACTIONS.len() {
+                BASE_ACTIONS[index]
+            } else {
+                Action::Bookmark(index - BASE_ACTIONS.len())
+            };
+            index += 1;
+        }
+        all
+    };
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Action::PanUp => "Pan up",
+            Action::PanLeft => "Pan left",
+            Action::PanDown => "Pan down",
+            Action::PanRight => "Pan right",
+            Action::DragPan => "Drag to pan",
+            Action::Select => "Select",
+            Action::Follow => "Follow",
+            Action::FollowNext => "Follow next body",
+            Action::FollowPrevious => "Follow previous body",
+            Action::SpawnBody => "Spawn body",
+            Action::Cancel => "Cancel",
+            Action::DeleteSelection => "Delete selection",
+            Action::FitAll => "Fit all bodies",
+            Action::ToggleRuler => "Toggle ruler",
+            Action::PlayPause => "Play/pause",
+            Action::Step => "Step",
+            Action::AddToSelection => "Add to selection",
+            Action::SaveBookmark => "Save bookmark (hold)",
+            Action::Pan3d => "Pan 3D view (hold)",
+            Action::Bookmark(index) => BOOKMARK_LABELS.get(*index).unwrap_or(&"Bookmark"),
+        }
+    }
+
+    /// Whether the action is only held together with other actions, so it can be bound to
+    /// modifier keys on their own
+    pub fn is_modifier(&self) -> bool {
+        matches!(
+            self,
+            Action::AddToSelection | Action::SaveBookmark | Action::Pan3d
+        )
+    }
+}
+
+const BOOKMARK_LABELS: [&str; BOOKMARK_COUNT] = [
+    "Bookmark 1",
+    "Bookmark 2",
+    "Bookmark 3",
+    "Bookmark 4",
+    "Bookmark 5",
+    "Bookmark 6",
+    "Bookmark 7",
+    "Bookmark 8",
+    "Bookmark 9",
+];
+
+const BOOKMARK_KEYS: [KeyCode; BOOKMARK_COUNT] = [
+    KeyCode::Digit1,
+    KeyCode::Digit2,
+    KeyCode::Digit3,
+    KeyCode::Digit4,
+    KeyCode::Digit5,
+    KeyCode::Digit6,
+    KeyCode::Digit7,
+    KeyCode::Digit8,
+    KeyCode::Digit9,
+];
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+pub enum InputButton {
+    Key(KeyCode),
+    Mouse(MouseButton),
+}
+
+/// A button, optionally combined with control
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+pub struct Binding {
+    pub button: InputButton,
+    #[serde(default)]
+    pub ctrl: bool,
+}
+
+impl Binding {
+    pub const fn key(key: KeyCode) -> Self {
+        Self {
+            button: InputButton::Key(key),
+            ctrl: false,
+        }
+    }
+
+    pub const fn mouse(button: MouseButton) -> Self {
+        Self {
+            button: InputButton::Mouse(button),
+            ctrl: false,
+        }
+    }
+
+    pub const fn with_ctrl(self) -> Self {
+        Self { ctrl: true, ..self }
+    }
+
+    pub fn label(&self) -> String {
+        let button = match self.button {
+            InputButton::Key(key) => {
+                let name = format!("{key:?}");
+                name.strip_prefix("Key")
+                    .or_else(|| name.strip_prefix("Digit"))
+                    .unwrap_or(&name)
+                    .to_string()
+            }
+            InputButton::Mouse(button) => format!("Mouse {button:?}"),
+        };
+
+        if self.ctrl {
+            format!("Ctrl + {button}")
+        } else {
+            button
+        }
+    }
+}
+
+/// The bindings of every [`Action`], any of which triggers it
+#[derive(Resource, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct InputMap(pub BTreeMap<Action, Vec<Binding>>);
+
+impl Default for InputMap {
+    fn default() -> Self {
+        let mut map = BTreeMap::from([
+            (Action::PanUp, vec![Binding::key(KeyCode::KeyW)]),
+            (Action::PanLeft, vec![Binding::key(KeyCode::KeyA)]),
+            (Action::PanDown, vec![Binding::key(KeyCode::KeyS)]),
+            (Action::PanRight, vec![Binding::key(KeyCode::KeyD)]),
+            (Action::DragPan, vec![Binding::mouse(MouseButton::Left)]),
+            (Action::Select, vec![Binding::mouse(MouseButton::Left)]),
+            (Action::Follow, vec![Binding::mouse(MouseButton::Middle)]),
+            (
+                Action::SpawnBody,
+                vec![Binding::key(KeyCode::KeyN).with_ctrl()],
+            ),
+            (Action::Cancel, vec![Binding::key(KeyCode::Escape)]),
+            (Action::DeleteSelection, vec![Binding::key(KeyCode::Delete)]),
+            (Action::FitAll, vec![Binding::key(KeyCode::KeyF)]),
+            (Action::ToggleRuler, vec![Binding::key(KeyCode::KeyM)]),
+            (Action::PlayPause, vec![Binding::key(KeyCode::Space)]),
+            (Action::Step, vec![Binding::key(KeyCode::ArrowRight)]),
+            (
+                Action::AddToSelection,
+                vec![
+                    Binding::key(KeyCode::ShiftLeft),
+                    Binding::key(KeyCode::ShiftRight),
+                ],
+            ),
+            (
+                Action::SaveBookmark,
+                vec![Binding::key(KeyCode::ControlLeft)],
+            ),
+            (Action::Pan3d, vec![Binding::key(KeyCode::AltLeft)]),
+        ]);
+        map.extend(
+            BOOKMARK_KEYS
+                .iter()
+                .enumerate()
+                .map(|(index, key)| (Action::Bookmark(index), vec![Binding::key(*key)])),
+        );
+        Self(map)
+    }
+}
+
+impl InputMap {
+    /// The bindings of the action joined for display, if it has any
+    pub fn label(&self, action: Action) -> Option<String> {
+        let bindings = self
+            .0
+            .get(&action)
+            .filter(|bindings| !bindings.is_empty())?;
+        Some(
+            bindings
+                .iter()
+                .map(Binding::label)
+                .collect::<Vec<_>>()
+                .join(" / "),
+        )
+    }
+
+    /// Actions that share a binding with another action they could clash with.
+    /// Mouse buttons used for both dragging and clicking are fine.
+    pub fn conflicts(&self, action: Action) -> Vec<Action> {
+        let Some(bindings) = self.0.get(&action) else {
+            return Vec::new();
+        };
+
+        self.0
+            .iter()
+            .filter(|(other, other_bindings)| {
+                **other != action
+                    && other_bindings.iter().any(|other| bindings.contains(other))
+                    && ![action, **other].contains(&Action::DragPan)
+            })
+            .map(|(other, _)| *other)
+            .collect()
+    }
+}
+
+#[derive(Clone, Copy)]
+enum ButtonState {
+    Pressed,
+    JustPressed,
+    JustReleased,
+}
+
+impl ButtonState {
+    fn of<T: Copy + Eq + Hash + Send + Sync + 'static>(
+        self,
+        input: &ButtonInput<T>,
+        button: T,
+    ) -> bool {
+        match self {
+            ButtonState::Pressed => input.pressed(button),
+            ButtonState::JustPressed => input.just_pressed(button),
+            ButtonState::JustReleased => input.just_released(button),
+        }
+    }
+}
+
+/// The gamepad button of every [`Action`] that has one
+#[derive(Resource, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GamepadMap(pub BTreeMap<Action, GamepadButtonType>);
+
+impl Default for GamepadMap {
+    fn default() -> Self {
+        Self(BTreeMap::from([
+            (Action::PlayPause, GamepadButtonType::South),
+            (Action::Step, GamepadButtonType::East),
+            (Action::FitAll, GamepadButtonType::North),
+            (Action::Cancel, GamepadButtonType::West),
+            (Action::FollowPrevious, GamepadButtonType::LeftTrigger),
+            (Action::FollowNext, GamepadButtonType::RightTrigger),
+        ]))
+    }
+}
+
+/// Reads the state of [`Action`]s through the [`InputMap`] and [`GamepadMap`], from the keyboard,
+/// mouse and every connected gamepad
+#[derive(SystemParam)]
+pub struct ActionInput<'w> {
+    map: Res<'w, InputMap>,
+    gamepad_map: Res<'w, GamepadMap>,
+    pub keyboard: Res<'w, ButtonInput<KeyCode>>,
+    pub mouse: Res<'w, ButtonInput<MouseButton>>,
+    gamepads: Res<'w, Gamepads>,
+    gamepad_buttons: Res<'w, ButtonInput<GamepadButton>>,
+}
+
+impl ActionInput<'_> {
+    fn ctrl_pressed(&self) -> bool {
+        self.keyboard
+            .any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight])
+    }
+
+    fn check(&self, action: Action, state: ButtonState) -> bool {
+        let bound = self.map.0.get(&action).is_some_and(|bindings| {
+            bindings.iter().any(|binding| {
+                (!binding.ctrl || self.ctrl_pressed())
+                    && match binding.button {
+                        InputButton::Key(key) => state.of(&self.keyboard, key),
+                        InputButton::Mouse(button) => state.of(&self.mouse, button),
+                    }
+            })
+        });
+        let gamepad = self.gamepad_map.0.get(&action).is_some_and(|button_type| {
+            self.gamepads.iter().any(|gamepad| {
+                state.of(
+                    &self.gamepad_buttons,
+                    GamepadButton::new(gamepad, *button_type),
+                )
+            })
+        });
+
+        bound || gamepad
+    }
+
+    pub fn pressed(&self, action: Action) -> bool {
+        self.check(action, ButtonState::Pressed)
+    }
+
+    pub fn just_pressed(&self, action: Action) -> bool {
+        self.check(action, ButtonState::JustPressed)
+    }
+
+    pub fn just_released(&self, action: Action) -> bool {
+        self.check(action, ButtonState::JustReleased)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use bevy::{
+        input::{
+            gamepad::{
+                GamepadButtonChangedEvent, GamepadConnection, GamepadConnectionEvent, GamepadEvent,
+                GamepadInfo,
+            },
+            InputPlugin,
+        },
+        state::app::StatesPlugin,
+    };
+
+    use super::*;
+    use crate::{
+        controls,
+        sim::{self, Follow, SimState, Trajectory},
+    };
+
+    #[derive(Resource, Default)]
+    struct Steps(usize);
+
+    const GAMEPAD: Gamepad = Gamepad { id: 0 };
+
+    fn app() -> App {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, InputPlugin, StatesPlugin))
+            .init_resource::<InputMap>()
+            .init_resource::<GamepadMap>()
+            .init_resource::<Steps>()
+            .insert_state(SimState::Paused)
+            .add_systems(
+                Update,
+                (sim::handle_input, controls::cam_controller_cycle_follow),
+            );
+
+        let step = app.register_system(|mut steps: ResMut<Steps>| steps.0 += 1);
+        app.insert_resource(sim::OneShotSystems(HashMap::from([(
+            "update_positions".to_string(),
+            step,
+        )])));
+
+        app.world_mut()
+            .send_event(GamepadEvent::Connection(GamepadConnectionEvent::new(
+                GAMEPAD,
+                GamepadConnection::Connected(GamepadInfo {
+                    name: "Test gamepad".to_string(),
+                }),
+            )));
+        app.update();
+        app
+    }
+
+    fn set_button(app: &mut App, button_type: GamepadButtonType, value: f32) {
+        app.world_mut()
+            .send_event(GamepadEvent::Button(GamepadButtonChangedEvent::new(
+                GAMEPAD,
+                button_type,
+                value,
+            )));
+        app.update();
+    }
+
+    fn tap(app: &mut App, button_type: GamepadButtonType) {
+        set_button(app, button_type, 1.0);
+        set_button(app, button_type, 0.0);
+    }
+
+    fn sim_state(app: &App) -> SimState {
+        app.world().resource::<State<SimState>>().get().clone()
+    }
+
+    fn followed(app: &mut App) -> Vec<String> {
+        app.world_mut()
+            .query_filtered::<&sim::Name, With<Follow>>()
+            .iter(app.world())
+            .map(|name| name.0.clone())
+            .collect()
+    }
+
+    #[test]
+    fn south_toggles_playback() {
+        let mut app = app();
+
+        tap(&mut app, GamepadButtonType::South);
+        assert_eq!(sim_state(&app), SimState::Playing);
+
+        tap(&mut app, GamepadButtonType::South);
+        assert_eq!(sim_state(&app), SimState::Paused);
+    }
+
+    #[test]
+    fn east_steps() {
+        let mut app = app();
+
+        set_button(&mut app, GamepadButtonType::East, 1.0);
+        assert_eq!(app.world().resource::<Steps>().0, 1);
+        // stepping repeats while the button is held
+        app.update();
+        assert_eq!(app.world().resource::<Steps>().0, 2);
+
+        set_button(&mut app, GamepadButtonType::East, 0.0);
+        app.update();
+        assert_eq!(app.world().resource::<Steps>().0, 2);
+        assert_eq!(sim_state(&app), SimState::Paused);
+    }
+
+    #[test]
+    fn shoulder_buttons_cycle_follow() {
+        let mut app = app();
+        for name in ["Charlie", "Alpha", "Bravo"] {
+            app.world_mut().spawn((
+                sim::Name(name.to_string()),
+                Trajectory::new(Vec2::ZERO, Vec2::ZERO),
+            ));
+        }
+
+        tap(&mut app, GamepadButtonType::RightTrigger);
+        assert_eq!(followed(&mut app), ["Alpha"]);
+        tap(&mut app, GamepadButtonType::RightTrigger);
+        assert_eq!(followed(&mut app), ["Bravo"]);
+
+        tap(&mut app, GamepadButtonType::LeftTrigger);
+        assert_eq!(followed(&mut app), ["Alpha"]);
+        // wraps around to the last body
+        tap(&mut app, GamepadButtonType::LeftTrigger);
+        assert_eq!(followed(&mut app), ["Charlie"]);
+    }
+
+    #[test]
+    fn default_bindings_do_not_conflict() {
+        let map = InputMap::default();
+        for action in Action::ALL {
+            assert_eq!(map.conflicts(action), [], "{action:?}");
+        }
+    }
+
+    #[test]
+    fn conflicts_with_any_shared_binding() {
+        let mut map = InputMap::default();
+        map.0
+            .get_mut(&Action::Pan3d)
+            .unwrap()
+            .push(Binding::key(KeyCode::ShiftRight));
+
+        assert_eq!(map.conflicts(Action::Pan3d), [Action::AddToSelection]);
+        assert_eq!(map.conflicts(Action::AddToSelection), [Action::Pan3d]);
+    }
+
+    #[test]
+    fn all_actions_end_with_every_bookmark() {
+        let bookmarks = &Action::ALL[Action::ALL.len() - BOOKMARK_COUNT..];
+        assert!(bookmarks
+            .iter()
+            .enumerate()
+            .all(|(index, action)| *action == Action::Bookmark(index)));
+        assert!(!Action::ALL[..Action::ALL.len() - BOOKMARK_COUNT]
+            .iter()
+            .any(|action| matches!(action, Action::Bookmark(_))));
+    }
+}
@@ -2,9 +2,14 @@ use bevy::{asset::LoadedFolder, prelude::*};
 
 mod assets;
 mod controls;
+mod input;
+mod overlays;
+mod selection;
+mod settings;
 mod sim;
 mod ui;
 pub mod utils;
+mod view3d;
 
 #[derive(States, Clone, Debug, PartialEq, Eq, Hash)]
 pub enum AppState {
@@ -66,7 +71,7 @@ fn load_next_sim(
 ) {
     if let AppState::SwitchSim { next_sim_id } = app_state.get() {
         let system = systems.get(*next_sim_id).expect("Invalid Asset Id");
-        sim_data.gravitational_const = system.gravitational_const;
+        sim_data.load_constants(system);
         sim_data.trajectory_pos = 1;
 
         next_app_state.set(AppState::Loading);
@@ -89,7 +94,7 @@ fn recieve_app_events(
             match app_state.get() {
                 AppState::MainMenu => {
                     let system = systems.get(*id).expect("Invalid Asset Id");
-                    sim_data.gravitational_const = system.gravitational_const;
+                    sim_data.load_constants(system);
 
                     next_app_state.set(AppState::Loading);
                     app_data.system_assets =
@@ -116,9 +121,13 @@ fn main() {
         .insert_state(AppState::MainMenu)
         .insert_resource(AppData::default())
         .add_systems(Startup, load_systems)
+        .add_plugins(settings::SettingsPlugin)
         .add_plugins(sim::SimulationPlugin)
+        .add_plugins(selection::SelectionPlugin)
+        .add_plugins(overlays::OverlaysPlugin)
         .add_plugins(ui::UiPlugin)
         .add_plugins(controls::ControlsPlugin)
+        .add_plugins(view3d::View3dPlugin)
         .add_systems(
             Update,
             (
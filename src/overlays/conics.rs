@@ -0,0 +1,96 @@
+use core::f32::consts::TAU;
+
+use bevy::prelude::*;
+
+use crate::sim::{
+    frame::CurrentFrame,
+    orbit::{self, OrbitalElements},
+    Mass, SimData, Trajectory, TrajectoryVisibility,
+};
+
+const RESOLUTION: usize = 128;
+
+/// Multiple of the current distance or periapsis at which hyperbolas are cut off
+const HYPERBOLA_EXTENT: f32 = 4.0;
+
+/// Settings of the overlay drawing every body's two-body orbit around its dominant attractor
+#[derive(Resource, Default)]
+pub struct ConicOverlay {
+    pub enabled: bool,
+}
+
+fn conic_points(elements: &OrbitalElements, current_distance: f32) -> Vec<Vec2> {
+    match elements.true_anomaly_limit() {
+        None => (0..=RESOLUTION)
+            .map(|i| elements.position_at(i as f32 / RESOLUTION as f32 * TAU))
+            .collect(),
+        Some(limit) => {
+            let max_distance = current_distance.max(elements.periapsis()) * HYPERBOLA_EXTENT;
+            (0..=RESOLUTION)
+                .map(|i| (i as f32 / RESOLUTION as f32 * 2.0 - 1.0) * limit)
+                .map(|true_anomaly| elements.position_at(true_anomaly))
+                .filter(|point| point.length() <= max_distance)
+                .collect()
+        }
+    }
+}
+
+pub(super) fn draw_conic_orbits(
+    overlay: Res<ConicOverlay>,
+    sim_data: Res<SimData>,
+    bodies: Query<(
+        &Trajectory,
+        &Mass,
+        &TrajectoryVisibility,
+        &Handle<ColorMaterial>,
+    )>,
+    mats: Res<Assets<ColorMaterial>>,
+    current_frame: Res<CurrentFrame>,
+    mut gizmos: Gizmos,
+) {
+    if !overlay.enabled {
+        return;
+    }
+
+    let states = bodies
+        .iter()
+        .filter_map(|(traj, Mass(mass), vis, mat_handle)| {
+            Some((traj.front()?, *mass, vis.0, mat_handle))
+        })
+        .collect::<Vec<_>>();
+    let parents = orbit::dominant_attractors(
+        &states
+            .iter()
+            .map(|(current, mass, ..)| (current.position3(), *mass))
+            .collect::<Vec<_>>(),
+    );
+
+    for ((current, mass, visible, mat_handle), parent) in states.iter().zip(parents) {
+        let Some((parent, parent_mass, ..)) = parent.map(|parent| states[parent]) else {
+            continue;
+        };
+        if !visible {
+            continue;
+        }
+
+        let rel_pos = current.position - parent.position;
+        let rel_vel = current.velocity - parent.velocity;
+        let mu = sim_data.gravitational_const * (mass + parent_mass);
+
+        let Some(elements) = OrbitalElements::from_state(rel_pos, rel_vel, mu) else {
+            continue;
+        };
+
+        let color = mats
+            .get(*mat_handle)
+            .map_or(Color::WHITE, |mat| mat.color)
+            .with_alpha(0.4);
+
+        gizmos.linestrip_2d(
+            conic_points(&elements, rel_pos.length())
+                .into_iter()
+                .map(|point| current_frame.0.pos_in_frame(parent.position + point)),
+            color,
+        );
+    }
+}
@@ -0,0 +1,281 @@
+use core::f32::consts::FRAC_PI_3;
+
+use bevy::{
+    color::palettes::css,
+    prelude::*,
+    sprite::{MaterialMesh2dBundle, Mesh2dHandle},
+    window::PrimaryWindow,
+};
+
+use crate::{
+    controls::SimCamera,
+    input::{Action, ActionInput},
+    sim::{
+        frame::{CurrentFrame, FrameSnapshot},
+        CelestialBody, ClearTrajectories, Mass, Name, Radius, SimSnapshot, Trajectory,
+        TrajectoryVisibility,
+    },
+};
+
+const MARKER_SIZE: f32 = 6.0;
+
+const TEST_BODY_MASS_RATIO: f32 = 1e-6;
+
+pub const LAGRANGE_POINT_NAMES: [&str; 5] = ["L1", "L2", "L3", "L4", "L5"];
+
+/// Settings of the Lagrange point, Hill sphere and Roche limit overlay
+#[derive(Resource)]
+pub struct LagrangeOverlay {
+    pub enabled: bool,
+    /// The primary and secondary body the overlay is computed for
+    pub pair: Option<(Entity, Entity)>,
+    pub show_points: bool,
+    pub show_hill_sphere: bool,
+    pub show_roche_limit: bool,
+}
+
+impl Default for LagrangeOverlay {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            pair: None,
+            show_points: true,
+            show_hill_sphere: true,
+            show_roche_limit: true,
+        }
+    }
+}
+
+/// Positions of L1 to L5 in the co-rotating frame of a pair (see
+/// [`FrameSnapshot::co_rotating`]), in units of the separation of the two bodies.
+/// `mass_ratio` is the secondary's share of the total mass.
+pub fn lagrange_points(mass_ratio: f32) -> [Vec2; 5] {
+    const EPSILON: f32 = 1e-6;
+    let mu = mass_ratio;
+
+    // net acceleration along the x axis of the rotating frame. It is strictly increasing between
+    // and beyond the two bodies, so each of the collinear points can be found by bisection.
+    let accel = |x: f32| {
+        let to_primary = x + mu;
+        let to_secondary = x - 1.0 + mu;
+        x - (1.0 - mu) * to_primary.signum() / to_primary.powi(2)
+            - mu * to_secondary.signum() / to_secondary.powi(2)
+    };
+    let bisect = |mut low: f32, mut high: f32| {
+        for _ in 0..64 {
+            let mid = (low + high) / 2.0;
+            if accel(mid) < 0.0 {
+                low = mid;
+            } else {
+                high = mid;
+            }
+        }
+        (low + high) / 2.0
+    };
+
+    let primary = Vec2::new(-mu, 0.0);
+
+    [
+        Vec2::new(bisect(-mu + EPSILON, 1.0 - mu - EPSILON), 0.0),
+        Vec2::new(bisect(1.0 - mu + EPSILON, 2.0), 0.0),
+        Vec2::new(bisect(-2.0, -mu - EPSILON), 0.0),
+        primary + Vec2::from_angle(FRAC_PI_3),
+        primary + Vec2::from_angle(-FRAC_PI_3),
+    ]
+}
+
+/// Radius of the secondary's Hill sphere
+pub fn hill_radius(separation: f32, primary_mass: f32, secondary_mass: f32) -> f32 {
+    separation * (secondary_mass / (3.0 * primary_mass)).cbrt()
+}
+
+/// Distance from the primary within which a rigid secondary would be torn apart by tidal forces
+pub fn roche_limit(secondary_radius: f32, primary_mass: f32, secondary_mass: f32) -> f32 {
+    secondary_radius * (2.0 * primary_mass / secondary_mass).cbrt()
+}
+
+/// Lagrange points, Hill sphere and Roche limit of a primary/secondary pair at the current step
+pub struct PairGeometry {
+    /// The co-rotating frame of the pair
+    pub frame: FrameSnapshot,
+    pub primary: Vec2,
+    pub secondary: Vec2,
+    rotating_points: [Vec2; 5],
+    pub hill_radius: f32,
+    pub roche_limit: f32,
+}
+
+impl PairGeometry {
+    pub fn new(
+        (primary, primary_mass): (SimSnapshot, f32),
+        (secondary, secondary_mass, secondary_radius): (SimSnapshot, f32, f32),
+    ) -> Option<Self> {
+        let total_mass = primary_mass + secondary_mass;
+        if primary_mass <= 0.0 || secondary_mass <= 0.0 {
+            return None;
+        }
+
+        let separation = primary.position.distance(secondary.position);
+
+        Some(Self {
+            frame: FrameSnapshot::co_rotating((primary, primary_mass), (secondary, secondary_mass)),
+            primary: primary.position,
+            secondary: secondary.position,
+            rotating_points: lagrange_points(secondary_mass / total_mass)
+                .map(|point| point * separation),
+            hill_radius: hill_radius(separation, primary_mass, secondary_mass),
+            roche_limit: roche_limit(secondary_radius, primary_mass, secondary_mass),
+        })
+    }
+
+    /// Inertial positions of L1 to L5
+    pub fn lagrange_points(&self) -> [Vec2; 5] {
+        self.rotating_points
+            .map(|point| self.frame.pos_in_inertial(point))
+    }
+
+    /// Inertial position and velocity of something at rest at the `index`th Lagrange point of the
+    /// co-rotating frame
+    pub fn lagrange_state(&self, index: usize) -> SimSnapshot {
+        let point = self.rotating_points[index];
+        SimSnapshot {
+            position: self.frame.pos_in_inertial(point),
+            velocity: self.frame.vel_in_inertial(point, Vec2::ZERO),
+            ..default()
+        }
+    }
+}
+
+/// Computes the [`PairGeometry`] for the pair chosen in the overlay, if it's enabled and both
+/// bodies exist
+pub fn pair_geometry(
+    overlay: &LagrangeOverlay,
+    bodies: &Query<(&Trajectory, &Mass, &Radius)>,
+) -> Option<PairGeometry> {
+    let (primary, secondary) = overlay.pair.filter(|_| overlay.enabled)?;
+    let (primary_traj, Mass(primary_mass), _) = bodies.get(primary).ok()?;
+    let (secondary_traj, Mass(secondary_mass), Radius(secondary_radius)) =
+        bodies.get(secondary).ok()?;
+
+    PairGeometry::new(
+        (primary_traj.front()?, *primary_mass),
+        (secondary_traj.front()?, *secondary_mass, *secondary_radius),
+    )
+}
+
+pub(super) fn draw_lagrange_overlay(
+    overlay: Res<LagrangeOverlay>,
+    bodies: Query<(&Trajectory, &Mass, &Radius)>,
+    current_frame: Res<CurrentFrame>,
+    q_projection: Query<&OrthographicProjection, With<SimCamera>>,
+    mut gizmos: Gizmos,
+) {
+    let Some(geometry) = pair_geometry(&overlay, &bodies) else {
+        return;
+    };
+    let frame = current_frame.0;
+    let marker_size = MARKER_SIZE * q_projection.single().scale;
+
+    if overlay.show_points {
+        for point in geometry.lagrange_points() {
+            let point = frame.pos_in_frame(point);
+            gizmos.circle_2d(point, marker_size, css::GOLD);
+            gizmos.line_2d(
+                point - Vec2::X * marker_size * 0.5,
+                point + Vec2::X * marker_size * 0.5,
+                css::GOLD,
+            );
+            gizmos.line_2d(
+                point - Vec2::Y * marker_size * 0.5,
+                point + Vec2::Y * marker_size * 0.5,
+                css::GOLD,
+            );
+        }
+    }
+
+    if overlay.show_hill_sphere {
+        gizmos
+            .circle_2d(
+                frame.pos_in_frame(geometry.secondary),
+                geometry.hill_radius,
+                css::DEEP_SKY_BLUE.with_alpha(0.6),
+            )
+            .resolution(128);
+    }
+
+    if overlay.show_roche_limit {
+        gizmos
+            .circle_2d(
+                frame.pos_in_frame(geometry.primary),
+                geometry.roche_limit,
+                css::ORANGE_RED.with_alpha(0.6),
+            )
+            .resolution(128);
+    }
+}
+
+/// Places a test body at a Lagrange point when its marker is clicked, moving along with the
+/// co-rotating frame
+#[allow(clippy::too_many_arguments)]
+pub(super) fn spawn_at_lagrange_point(
+    overlay: Res<LagrangeOverlay>,
+    input: ActionInput,
+    q_windows: Query<&Window, With<PrimaryWindow>>,
+    q_camera: Query<(&Camera, &GlobalTransform, &OrthographicProjection), With<SimCamera>>,
+    bodies: Query<(&Trajectory, &Mass, &Radius)>,
+    current_frame: Res<CurrentFrame>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut clear_traj_evw: EventWriter<ClearTrajectories>,
+    mut cmds: Commands,
+) {
+    if !overlay.show_points || !input.just_pressed(Action::Select) {
+        return;
+    }
+    let Some(geometry) = pair_geometry(&overlay, &bodies) else {
+        return;
+    };
+
+    let (cam, cam_global_transform, projection) = q_camera.single();
+    let Some(cursor_pos) = q_windows
+        .single()
+        .cursor_position()
+        .and_then(|pos| cam.viewport_to_world_2d(cam_global_transform, pos))
+    else {
+        return;
+    };
+
+    let marker_size = MARKER_SIZE * projection.scale;
+
+    let Some(index) = geometry.lagrange_points().iter().position(|point| {
+        current_frame.0.pos_in_frame(*point).distance(cursor_pos) < marker_size * 1.5
+    }) else {
+        return;
+    };
+
+    let (_, secondary) = overlay.pair.unwrap();
+    let (_, Mass(secondary_mass), Radius(secondary_radius)) = bodies.get(secondary).unwrap();
+    let radius = secondary_radius * 0.5;
+    let state = geometry.lagrange_state(index);
+
+    let transform =
+        Transform::from_translation(current_frame.0.pos_in_frame(state.position).extend(0.0))
+            .with_scale(Vec3::new(radius, radius, 0.0));
+
+    cmds.spawn(MaterialMesh2dBundle {
+        mesh: Mesh2dHandle(meshes.add(Circle::default())),
+        material: materials.add(Color::WHITE),
+        transform,
+        ..default()
+    })
+    .insert(CelestialBody {
+        name: Name(format!("{} test body", LAGRANGE_POINT_NAMES[index])),
+        mass: Mass(secondary_mass * TEST_BODY_MASS_RATIO),
+        transform,
+        radius: Radius(radius),
+        trajectory: Trajectory::new(state.position, state.velocity),
+        trajectory_visibility: TrajectoryVisibility(true),
+    });
+
+    clear_traj_evw.send(ClearTrajectories);
+}
@@ -0,0 +1,326 @@
+use bevy::{color::palettes::css, prelude::*, window::PrimaryWindow};
+
+use crate::{
+    controls::SimCamera,
+    input::{Action, ActionInput},
+    sim::{frame::CurrentFrame, Radius, Trajectory},
+};
+
+const GRID_MIN_SPACING: f32 = 40.0;
+
+const SNAP_DISTANCE: f32 = 8.0;
+
+const CLICK_TOLERANCE: f32 = 4.0;
+
+const METRES_PER_AU: f32 = 1.495_978_7e11;
+const METRES_PER_LIGHT_YEAR: f32 = 9.460_731e15;
+/// A Julian year
+const SECONDS_PER_YEAR: u64 = 31_557_600;
+
+/// Settings of the coordinate grid, scale bar and ruler
+#[derive(Resource)]
+pub struct MeasureOverlay {
+    pub show_grid: bool,
+    pub show_scale_bar: bool,
+    /// Show distances and velocities in SI units instead of simulation units
+    pub physical_units: bool,
+    pub metres_per_unit: f32,
+    pub seconds_per_unit: f32,
+}
+
+impl Default for MeasureOverlay {
+    fn default() -> Self {
+        Self {
+            show_grid: false,
+            show_scale_bar: true,
+            physical_units: false,
+            metres_per_unit: 1.0,
+            seconds_per_unit: 1.0,
+        }
+    }
+}
+
+impl MeasureOverlay {
+    pub fn format_distance(&self, distance: f32) -> String {
+        if !self.physical_units {
+            return format!("{distance:.3}");
+        }
+
+        let metres = distance * self.metres_per_unit;
+        if metres.abs() < 1e3 {
+            format!("{metres:.3} m")
+        } else if metres.abs() < METRES_PER_AU * 0.1 {
+            format!("{:.3} km", metres / 1e3)
+        } else if metres.abs() < METRES_PER_LIGHT_YEAR * 0.1 {
+            format!("{:.3} AU", metres / METRES_PER_AU)
+        } else {
+            format!("{:.3} ly", metres / METRES_PER_LIGHT_YEAR)
+        }
+    }
+
+    pub fn format_speed(&self, speed: f32) -> String {
+        if !self.physical_units {
+            return format!("{speed:.3}");
+        }
+
+        let metres_per_second = speed * self.metres_per_unit / self.seconds_per_unit;
+        if metres_per_second.abs() < 1e3 {
+            format!("{metres_per_second:.3} m/s")
+        } else {
+            format!("{:.3} km/s", metres_per_second / 1e3)
+        }
+    }
+
+    /// The simulation time as years, days and time of day, if physical units are shown
+    pub fn format_time(&self, time: f64) -> Option<String> {
+        if !self.physical_units {
+            return None;
+        }
+
+        let seconds = time * self.seconds_per_unit as f64;
+        let (sign, seconds) = if seconds < 0.0 {
+            ("-", -seconds)
+        } else {
+            ("", seconds)
+        };
+        let total = seconds.floor() as u64;
+        let (years, rest) = (total / SECONDS_PER_YEAR, total % SECONDS_PER_YEAR);
+        let (days, rest) = (rest / 86400, rest % 86400);
+        let (hours, minutes, secs) = (rest / 3600, rest / 60 % 60, rest % 60);
+
+        Some(if years > 0 {
+            format!("{sign}{years}y {days}d {hours:02}:{minutes:02}:{secs:02}")
+        } else {
+            format!("{sign}{days}d {hours:02}:{minutes:02}:{secs:02}")
+        })
+    }
+
+    /// The longest round distance in simulation units that is at most `max_length` long, in the
+    /// units that are shown
+    pub fn round_distance(&self, max_length: f32) -> f32 {
+        let unit = if self.physical_units {
+            self.metres_per_unit
+        } else {
+            1.0
+        };
+        let max_length = max_length * unit;
+        let magnitude = 10f32.powf(max_length.log10().floor());
+
+        let factor = [5.0, 2.0, 1.0]
+            .into_iter()
+            .find(|factor| factor * magnitude <= max_length)
+            .unwrap_or(1.0);
+        factor * magnitude / unit
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RulerEnd {
+    /// A fixed point in the current reference frame
+    Point(Vec2),
+    Body(Entity),
+}
+
+/// The distance measuring tool. Every click places the next end of the ruler while it's active.
+#[derive(Resource, Default)]
+pub struct Ruler {
+    pub active: bool,
+    pub start: Option<RulerEnd>,
+    pub end: Option<RulerEnd>,
+    press_pos: Option<Vec2>,
+}
+
+/// Positions of both ends of a [`Ruler`] and their relative velocity, as seen in the current
+/// reference frame
+pub struct Measurement {
+    pub start: Vec2,
+    pub end: Vec2,
+    pub relative_velocity: Vec2,
+}
+
+impl Measurement {
+    pub fn distance(&self) -> f32 {
+        self.start.distance(self.end)
+    }
+}
+
+impl Ruler {
+    pub fn clear(&mut self) {
+        self.start = None;
+        self.end = None;
+    }
+
+    fn resolve(
+        end: RulerEnd,
+        bodies: &Query<(Entity, &Trajectory, &Transform, &Radius)>,
+        current_frame: &CurrentFrame,
+    ) -> Option<(Vec2, Vec2)> {
+        match end {
+            RulerEnd::Point(point) => Some((point, Vec2::ZERO)),
+            RulerEnd::Body(entity) => {
+                let (_, trajectory, transform, _) = bodies.get(entity).ok()?;
+                let current = trajectory.front()?;
+                Some((
+                    transform.translation.xy(),
+                    current_frame
+                        .0
+                        .vel_in_frame(current.position, current.velocity),
+                ))
+            }
+        }
+    }
+
+    /// Measures between both ends, or between the start and `cursor` while the end hasn't been
+    /// placed yet
+    pub fn measure(
+        &self,
+        cursor: Option<Vec2>,
+        bodies: &Query<(Entity, &Trajectory, &Transform, &Radius)>,
+        current_frame: &CurrentFrame,
+    ) -> Option<Measurement> {
+        let (start, start_vel) = Self::resolve(self.start?, bodies, current_frame)?;
+        let (end, end_vel) = match self.end {
+            Some(end) => Self::resolve(end, bodies, current_frame)?,
+            None if self.active => (cursor?, Vec2::ZERO),
+            None => return None,
+        };
+
+        Some(Measurement {
+            start,
+            end,
+            relative_velocity: end_vel - start_vel,
+        })
+    }
+}
+
+/// Position of the cursor in world space
+pub fn cursor_world_pos(
+    q_windows: &Query<&Window, With<PrimaryWindow>>,
+    q_camera: &Query<(&Camera, &GlobalTransform, &OrthographicProjection), With<SimCamera>>,
+) -> Option<Vec2> {
+    let (cam, cam_global_transform, _) = q_camera.single();
+    q_windows
+        .single()
+        .cursor_position()
+        .and_then(|pos| cam.viewport_to_world_2d(cam_global_transform, pos))
+}
+
+pub(super) fn draw_grid(
+    overlay: Res<MeasureOverlay>,
+    q_camera: Query<(&GlobalTransform, &OrthographicProjection), With<SimCamera>>,
+    mut gizmos: Gizmos,
+) {
+    if !overlay.show_grid {
+        return;
+    }
+
+    let (cam_transform, projection) = q_camera.single();
+    let area = Rect::from_center_size(
+        cam_transform.translation().xy() + projection.area.center(),
+        projection.area.size(),
+    );
+
+    let spacing = 10f32.powf((GRID_MIN_SPACING * projection.scale).log10().ceil());
+
+    let lines = |min: f32, max: f32| {
+        ((min / spacing).floor() as i64..=(max / spacing).ceil() as i64)
+            .map(|i| (i, i as f32 * spacing))
+    };
+    let colour = |i: i64| {
+        // every tenth line is emphasized, and so is the origin
+        let alpha = if i == 0 {
+            0.4
+        } else if i % 10 == 0 {
+            0.2
+        } else {
+            0.07
+        };
+        css::LIGHT_GRAY.with_alpha(alpha)
+    };
+
+    for (i, x) in lines(area.min.x, area.max.x) {
+        gizmos.line_2d(
+            Vec2::new(x, area.min.y),
+            Vec2::new(x, area.max.y),
+            colour(i),
+        );
+    }
+    for (i, y) in lines(area.min.y, area.max.y) {
+        gizmos.line_2d(
+            Vec2::new(area.min.x, y),
+            Vec2::new(area.max.x, y),
+            colour(i),
+        );
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub(super) fn place_ruler_points(
+    mut ruler: ResMut<Ruler>,
+    input: ActionInput,
+    q_windows: Query<&Window, With<PrimaryWindow>>,
+    q_camera: Query<(&Camera, &GlobalTransform, &OrthographicProjection), With<SimCamera>>,
+    bodies: Query<(Entity, &Trajectory, &Transform, &Radius)>,
+) {
+    if input.just_pressed(Action::ToggleRuler) {
+        ruler.active = !ruler.active;
+    }
+    if !ruler.active {
+        return;
+    }
+    if input.just_pressed(Action::Cancel) {
+        ruler.clear();
+    }
+
+    let Some(cursor_pos) = cursor_world_pos(&q_windows, &q_camera) else {
+        return;
+    };
+    let scale = q_camera.single().2.scale;
+
+    if input.just_pressed(Action::Select) {
+        ruler.press_pos = Some(cursor_pos);
+    }
+    if !input.just_released(Action::Select) {
+        return;
+    }
+    // dragging pans the camera
+    match ruler.press_pos.take() {
+        Some(press_pos) if press_pos.distance(cursor_pos) <= CLICK_TOLERANCE * scale => {}
+        _ => return,
+    }
+
+    let point = bodies
+        .iter()
+        .find(|(_, _, transform, Radius(radius))| {
+            transform.translation.xy().distance(cursor_pos) < radius.max(SNAP_DISTANCE * scale)
+        })
+        .map_or(RulerEnd::Point(cursor_pos), |(entity, ..)| {
+            RulerEnd::Body(entity)
+        });
+
+    if ruler.start.is_none() || ruler.end.is_some() {
+        ruler.start = Some(point);
+        ruler.end = None;
+    } else {
+        ruler.end = Some(point);
+    }
+}
+
+pub(super) fn draw_ruler(
+    ruler: Res<Ruler>,
+    q_windows: Query<&Window, With<PrimaryWindow>>,
+    q_camera: Query<(&Camera, &GlobalTransform, &OrthographicProjection), With<SimCamera>>,
+    bodies: Query<(Entity, &Trajectory, &Transform, &Radius)>,
+    current_frame: Res<CurrentFrame>,
+    mut gizmos: Gizmos,
+) {
+    let cursor = cursor_world_pos(&q_windows, &q_camera);
+    let Some(measurement) = ruler.measure(cursor, &bodies, &current_frame) else {
+        return;
+    };
+    let marker_size = SNAP_DISTANCE * q_camera.single().2.scale * 0.5;
+
+    gizmos.line_2d(measurement.start, measurement.end, css::AQUA);
+    gizmos.circle_2d(measurement.start, marker_size, css::AQUA);
+    gizmos.circle_2d(measurement.end, marker_size, css::AQUA);
+}
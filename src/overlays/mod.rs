@@ -0,0 +1,45 @@
+use bevy::prelude::*;
+
+use crate::{sim::SimSystemSet, ui, utils, view3d, AppState};
+
+pub mod conics;
+pub mod lagrange;
+pub mod measure;
+pub mod potential;
+pub mod vectors;
+
+pub struct OverlaysPlugin;
+
+impl Plugin for OverlaysPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<lagrange::LagrangeOverlay>()
+            .init_resource::<conics::ConicOverlay>()
+            .init_resource::<vectors::VectorOverlay>()
+            .init_resource::<potential::PotentialOverlay>()
+            .init_resource::<measure::MeasureOverlay>()
+            .init_resource::<measure::Ruler>()
+            .add_systems(
+                Update,
+                (
+                    conics::draw_conic_orbits,
+                    lagrange::draw_lagrange_overlay,
+                    vectors::draw_vectors,
+                    potential::update_potential_layer,
+                    measure::draw_grid,
+                    measure::draw_ruler,
+                    lagrange::spawn_at_lagrange_point.run_if(not(ui::ui_is_active)),
+                    measure::place_ruler_points.run_if(not(ui::ui_is_active)),
+                )
+                    .in_set(SimSystemSet)
+                    // the overlays are drawn in the plane of the 2D view
+                    .run_if(view3d::in_2d),
+            )
+            .add_systems(
+                OnExit(AppState::Simulating),
+                (
+                    utils::cleanup::<potential::PotentialLayer>,
+                    |mut ruler: ResMut<measure::Ruler>| ruler.clear(),
+                ),
+            );
+    }
+}
@@ -0,0 +1,290 @@
+use bevy::{
+    prelude::*,
+    render::{
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+    },
+};
+
+use crate::{
+    controls::SimCamera,
+    sim::{Mass, Radius, SimData, Trajectory},
+};
+
+const LAYER_Z: f32 = -10.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FieldQuantity {
+    Potential,
+    FieldStrength,
+}
+
+impl FieldQuantity {
+    pub fn label(&self) -> &'static str {
+        match self {
+            FieldQuantity::Potential => "Potential",
+            FieldQuantity::FieldStrength => "Field strength",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColourMap {
+    Viridis,
+    Inferno,
+    Greyscale,
+}
+
+impl ColourMap {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ColourMap::Viridis => "Viridis",
+            ColourMap::Inferno => "Inferno",
+            ColourMap::Greyscale => "Greyscale",
+        }
+    }
+
+    fn stops(&self) -> &'static [[u8; 3]] {
+        match self {
+            ColourMap::Viridis => &[
+                [0x44, 0x01, 0x54],
+                [0x3b, 0x52, 0x8b],
+                [0x21, 0x91, 0x8c],
+                [0x5e, 0xc9, 0x62],
+                [0xfd, 0xe7, 0x25],
+            ],
+            ColourMap::Inferno => &[
+                [0x00, 0x00, 0x04],
+                [0x42, 0x0a, 0x68],
+                [0x93, 0x26, 0x67],
+                [0xdd, 0x51, 0x3a],
+                [0xfc, 0xa5, 0x0a],
+                [0xfc, 0xff, 0xa4],
+            ],
+            ColourMap::Greyscale => &[[0x00, 0x00, 0x00], [0xff, 0xff, 0xff]],
+        }
+    }
+
+    fn sample(&self, t: f32) -> [f32; 3] {
+        let stops = self.stops();
+        let scaled = t.clamp(0.0, 1.0) * (stops.len() - 1) as f32;
+        let index = (scaled as usize).min(stops.len() - 2);
+        let fraction = scaled - index as f32;
+
+        [0, 1, 2].map(|channel| {
+            let from = stops[index][channel] as f32;
+            let to = stops[index + 1][channel] as f32;
+            from + (to - from) * fraction
+        })
+    }
+}
+
+/// Settings of the background layer showing the gravitational potential or field strength over
+/// the visible area
+#[derive(Resource)]
+pub struct PotentialOverlay {
+    pub enabled: bool,
+    pub quantity: FieldQuantity,
+    pub colour_map: ColourMap,
+    pub contours: bool,
+    pub contour_count: u32,
+    /// Number of grid cells across the width of the viewport
+    pub resolution: u32,
+    pub opacity: f32,
+    /// Minimum time between two updates, in seconds
+    pub update_interval: f32,
+}
+
+impl Default for PotentialOverlay {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            quantity: FieldQuantity::Potential,
+            colour_map: ColourMap::Viridis,
+            contours: true,
+            contour_count: 12,
+            resolution: 160,
+            opacity: 0.6,
+            update_interval: 0.1,
+        }
+    }
+}
+
+#[derive(Component)]
+pub(super) struct PotentialLayer;
+
+/// What the layer was last computed from
+#[derive(Default)]
+pub(super) struct LayerInputs {
+    since_update: f32,
+    bodies: Vec<(Vec2, f32, f32)>,
+    area: Rect,
+}
+
+/// Distances are clamped to the radius of each body so the values stay finite inside of them
+fn sample_field(
+    point: Vec2,
+    bodies: &[(Vec2, f32, f32)],
+    gravitational_const: f32,
+    quantity: FieldQuantity,
+) -> f32 {
+    match quantity {
+        FieldQuantity::Potential => bodies
+            .iter()
+            .map(|(position, mass, radius)| {
+                gravitational_const * mass / point.distance(*position).max(*radius)
+            })
+            .sum::<f32>()
+            .ln(),
+        FieldQuantity::FieldStrength => bodies
+            .iter()
+            .map(|(position, mass, radius)| {
+                let offset = *position - point;
+                let distance = offset.length().max(*radius);
+                offset.normalize_or_zero() * gravitational_const * *mass / distance.powi(2)
+            })
+            .sum::<Vec2>()
+            .length()
+            .ln(),
+    }
+}
+
+fn render_field(overlay: &PotentialOverlay, values: &[f32], width: usize) -> Vec<u8> {
+    let (min, max) = values
+        .iter()
+        .filter(|value| value.is_finite())
+        .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), value| {
+            (min.min(*value), max.max(*value))
+        });
+    let range = (max - min).max(f32::EPSILON);
+
+    let normalized = values
+        .iter()
+        .map(|value| {
+            if value.is_finite() {
+                (value - min) / range
+            } else {
+                0.0
+            }
+        })
+        .collect::<Vec<_>>();
+    let band = |t: f32| (t * overlay.contour_count as f32).floor() as i32;
+
+    normalized
+        .iter()
+        .enumerate()
+        .flat_map(|(i, t)| {
+            let mut colour = overlay.colour_map.sample(*t);
+
+            // a contour runs between neighbouring cells in different bands
+            let right = (i % width + 1 < width).then_some(i + 1);
+            let below = Some(i + width);
+            let is_contour = overlay.contours
+                && [right, below]
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|neighbour| normalized.get(neighbour))
+                    .any(|other| band(*other) != band(*t));
+            if is_contour {
+                colour = colour.map(|channel| channel + (255.0 - channel) * 0.6);
+            }
+
+            let [r, g, b] = colour.map(|channel| channel as u8);
+            [r, g, b, (overlay.opacity.clamp(0.0, 1.0) * 255.0) as u8]
+        })
+        .collect()
+}
+
+/// Recomputes the layer over the visible area when the bodies, the camera or the settings change,
+/// at most once per [`PotentialOverlay::update_interval`]
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub(super) fn update_potential_layer(
+    overlay: Res<PotentialOverlay>,
+    sim_data: Res<SimData>,
+    time: Res<Time>,
+    bodies: Query<(&Transform, &Mass, &Radius), (With<Trajectory>, Without<PotentialLayer>)>,
+    q_camera: Query<(&GlobalTransform, &OrthographicProjection), With<SimCamera>>,
+    mut q_layer: Query<(Entity, &mut Transform, &mut Sprite, &Handle<Image>), With<PotentialLayer>>,
+    mut images: ResMut<Assets<Image>>,
+    mut inputs: Local<LayerInputs>,
+    mut cmds: Commands,
+) {
+    if !overlay.enabled {
+        for (entity, ..) in &q_layer {
+            cmds.entity(entity).despawn_recursive();
+        }
+        return;
+    }
+
+    let Ok((_, mut layer_transform, mut sprite, image_handle)) = q_layer.get_single_mut() else {
+        cmds.spawn(SpriteBundle {
+            texture: images.add(Image::default()),
+            ..default()
+        })
+        .insert(PotentialLayer);
+        *inputs = LayerInputs::default();
+        return;
+    };
+
+    inputs.since_update += time.delta_seconds();
+    if inputs.since_update < overlay.update_interval {
+        return;
+    }
+
+    let (cam_transform, projection) = q_camera.single();
+    let area = Rect::from_center_size(
+        cam_transform.translation().xy() + projection.area.center(),
+        projection.area.size(),
+    );
+    let current = bodies
+        .iter()
+        .map(|(transform, Mass(mass), Radius(radius))| (transform.translation.xy(), *mass, *radius))
+        .collect::<Vec<_>>();
+
+    if !overlay.is_changed() && inputs.bodies == current && inputs.area == area {
+        return;
+    }
+
+    let width = overlay.resolution.max(2) as usize;
+    let height = ((width as f32 * area.height() / area.width()).round() as usize).max(2);
+    let cell = area.size() / Vec2::new(width as f32, height as f32);
+
+    let values = (0..height)
+        .flat_map(|y| (0..width).map(move |x| (x, y)))
+        .map(|(x, y)| {
+            let point = Vec2::new(
+                area.min.x + (x as f32 + 0.5) * cell.x,
+                area.max.y - (y as f32 + 0.5) * cell.y,
+            );
+            sample_field(
+                point,
+                &current,
+                sim_data.gravitational_const,
+                overlay.quantity,
+            )
+        })
+        .collect::<Vec<_>>();
+
+    if let Some(image) = images.get_mut(image_handle) {
+        *image = Image::new(
+            Extent3d {
+                width: width as u32,
+                height: height as u32,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            render_field(&overlay, &values, width),
+            TextureFormat::Rgba8UnormSrgb,
+            RenderAssetUsages::default(),
+        );
+    }
+
+    sprite.custom_size = Some(area.size());
+    layer_transform.translation = area.center().extend(LAYER_Z);
+
+    *inputs = LayerInputs {
+        since_update: 0.0,
+        bodies: current,
+        area,
+    };
+}
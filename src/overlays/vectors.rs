@@ -0,0 +1,155 @@
+use bevy::{color::palettes::css, prelude::*};
+
+use crate::sim::{
+    self, forces::ForceSources, frame::CurrentFrame, BodyState, Mass, Radius, SimClock, SimData,
+    Trajectory,
+};
+
+/// Settings of the overlay drawing velocity, acceleration and force vectors of every body
+#[derive(Resource)]
+pub struct VectorOverlay {
+    pub show_velocity: bool,
+    pub show_acceleration: bool,
+    /// The contribution of every other body to a body's net force
+    pub show_forces: bool,
+    /// The contribution of gravity, the post-Newtonian correction, thrust and drag and radiation
+    /// pressure to a body's net force
+    pub show_sources: bool,
+    pub velocity_scale: f32,
+    pub acceleration_scale: f32,
+    pub force_scale: f32,
+    /// Scales the length of the arrows logarithmically with the magnitude, so that both tiny and
+    /// huge vectors stay readable
+    pub log_scale: bool,
+}
+
+impl Default for VectorOverlay {
+    fn default() -> Self {
+        Self {
+            show_velocity: false,
+            show_acceleration: false,
+            show_forces: false,
+            show_sources: false,
+            velocity_scale: 1.0,
+            acceleration_scale: 1.0,
+            force_scale: 0.01,
+            log_scale: false,
+        }
+    }
+}
+
+impl VectorOverlay {
+    fn arrow(&self, vector: Vec2, scale: f32) -> Vec2 {
+        let length = vector.length();
+        if length == 0.0 {
+            return Vec2::ZERO;
+        }
+
+        let arrow_length = if self.log_scale {
+            length.ln_1p()
+        } else {
+            length
+        };
+        vector / length * arrow_length * scale
+    }
+}
+
+pub(super) fn draw_vectors(
+    overlay: Res<VectorOverlay>,
+    sim_data: Res<SimData>,
+    clock: Res<SimClock>,
+    bodies: Query<(&Trajectory, &Mass, &Radius, ForceSources, &Transform)>,
+    current_frame: Res<CurrentFrame>,
+    mut gizmos: Gizmos,
+) {
+    if !(overlay.show_velocity
+        || overlay.show_acceleration
+        || overlay.show_forces
+        || overlay.show_sources)
+    {
+        return;
+    }
+
+    let frame = current_frame.0;
+    let time = SimClock::time_at(clock.steps) as f32;
+    let items = bodies
+        .iter()
+        .filter_map(|(traj, Mass(mass), Radius(radius), sources, transform)| {
+            Some((
+                traj.front()?,
+                *mass,
+                *radius,
+                sources,
+                transform.translation.xy(),
+            ))
+        })
+        .collect::<Vec<_>>();
+    let states = items
+        .iter()
+        .map(|(state, mass, radius, sources, _)| BodyState {
+            state: *state,
+            mass: *mass,
+            radius: *radius,
+            sources,
+        })
+        .collect::<Vec<_>>();
+
+    for (i, (body, (.., start))) in states.iter().zip(&items).enumerate() {
+        let current = body.state;
+
+        if overlay.show_velocity {
+            let velocity = frame.vel_in_frame(current.position, current.velocity);
+            gizmos.arrow_2d(
+                *start,
+                *start + overlay.arrow(velocity, overlay.velocity_scale),
+                css::LIME,
+            );
+        }
+
+        if overlay.show_forces {
+            for (j, other) in states.iter().enumerate() {
+                if i == j {
+                    continue;
+                }
+
+                let contribution = sim::pair_acceleration(&sim_data, body, other).total();
+                let force = frame.dir_in_frame(contribution.xy() * body.mass);
+                gizmos.arrow_2d(
+                    *start,
+                    *start + overlay.arrow(force, overlay.force_scale),
+                    css::GOLD.with_alpha(0.6),
+                );
+            }
+        }
+
+        let accel = sim::body_acceleration(&sim_data, &states, i, time);
+
+        if overlay.show_sources {
+            for (contribution, colour) in [
+                (accel.gravity, css::KHAKI),
+                (accel.post_newtonian, css::VIOLET),
+                (accel.thrust, css::ORANGE),
+                (accel.drag_and_radiation, css::DEEP_SKY_BLUE),
+            ] {
+                if contribution == Vec3::ZERO {
+                    continue;
+                }
+                let force = frame.dir_in_frame(contribution.xy() * body.mass);
+                gizmos.arrow_2d(
+                    *start,
+                    *start + overlay.arrow(force, overlay.force_scale),
+                    colour.with_alpha(0.6),
+                );
+            }
+        }
+
+        if overlay.show_acceleration {
+            let accel = frame.dir_in_frame(accel.total().xy());
+            gizmos.arrow_2d(
+                *start,
+                *start + overlay.arrow(accel, overlay.acceleration_scale),
+                css::TOMATO,
+            );
+        }
+    }
+}
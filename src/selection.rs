@@ -0,0 +1,176 @@
+use bevy::{prelude::*, sprite::MaterialMesh2dBundle, sprite::Mesh2dHandle};
+
+use crate::sim::{
+    appearance::{self, Appearance, GlowImage},
+    forces,
+    manoeuvre::Manoeuvres,
+    trajectory::TrajectoryStyle,
+    CelestialBody, ClearTrajectories, Follow, Mass, Name, Radius, SimSnapshot, SimSystemSet, Tag,
+    Trajectory, TrajectoryVisibility,
+};
+
+/// Marks a body as part of the current selection. Unlike [`Follow`] and
+/// [`Inspect`](crate::ui::Inspect), any number of bodies can be selected at once.
+#[derive(Component)]
+pub struct Selected;
+
+/// An operation applied to every [`Selected`] body.
+#[derive(Event, Clone, Copy, Debug)]
+pub enum SelectionEvent {
+    Delete,
+    /// Copies the selected bodies next to the originals and selects the copies.
+    Duplicate,
+    Translate(Vec2),
+    AddVelocity(Vec2),
+    ScaleMass(f32),
+    SetTrajectoryVisibility(bool),
+    /// Follows the centre of mass of the selected bodies.
+    FollowCentreOfMass,
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn apply_selection_events(
+    mut ev_reader: EventReader<SelectionEvent>,
+    mut q_selected: Query<
+        (
+            Entity,
+            &Name,
+            &mut Mass,
+            &Radius,
+            &mut Trajectory,
+            &mut TrajectoryVisibility,
+            &mut Transform,
+            &Handle<ColorMaterial>,
+            &Mesh2dHandle,
+        ),
+        With<Selected>,
+    >,
+    q_followed: Query<Entity, (With<Follow>, Without<Selected>)>,
+    q_styles: Query<&TrajectoryStyle>,
+    q_forces: Query<forces::ForceSources>,
+    q_manoeuvres: Query<&Manoeuvres>,
+    q_tags: Query<&Tag>,
+    q_appearances: Query<&Appearance>,
+    asset_server: Res<AssetServer>,
+    glow_image: Res<GlowImage>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut clear_traj_evw: EventWriter<ClearTrajectories>,
+    mut cmds: Commands,
+) {
+    for ev in ev_reader.read() {
+        if q_selected.is_empty() {
+            continue;
+        }
+
+        match *ev {
+            SelectionEvent::Delete => {
+                for (entity, ..) in q_selected.iter() {
+                    cmds.entity(entity).despawn_recursive();
+                }
+            }
+            SelectionEvent::Duplicate => {
+                for (entity, name, mass, radius, trajectory, vis, transform, mat, mesh) in
+                    q_selected.iter()
+                {
+                    let current = trajectory.front().expect("Trajectory empty");
+                    // place the copy beside the original so the two don't overlap
+                    let offset = Vec2::X * radius.0 * 3.0;
+                    let transform =
+                        transform.with_translation(transform.translation + offset.extend(0.0));
+                    let color = materials.get(mat).map_or(Color::WHITE, |mat| mat.color);
+
+                    let mut copy = cmds.spawn(MaterialMesh2dBundle {
+                        mesh: mesh.clone(),
+                        material: materials.add(color),
+                        transform,
+                        ..default()
+                    });
+                    if let Ok(style) = q_styles.get(entity) {
+                        copy.insert(style.clone());
+                    }
+                    if let Ok(manoeuvres) = q_manoeuvres.get(entity) {
+                        copy.insert(manoeuvres.clone());
+                    }
+                    if let Ok(tag) = q_tags.get(entity) {
+                        copy.insert(tag.clone());
+                    }
+                    if let Ok(sources) = q_forces.get(entity) {
+                        forces::BodyForces::from_sources(&sources).apply(&mut copy);
+                    }
+                    copy.insert(CelestialBody {
+                        name: Name(format!("{} (copy)", name.0)),
+                        mass: Mass(mass.0),
+                        transform,
+                        radius: Radius(radius.0),
+                        trajectory: Trajectory::from_snapshot(SimSnapshot {
+                            position: current.position + offset,
+                            ..current
+                        }),
+                        trajectory_visibility: TrajectoryVisibility(vis.0),
+                    })
+                    .insert(Selected);
+                    let copy = copy.id();
+
+                    if let Ok(appearance) = q_appearances.get(entity) {
+                        cmds.entity(copy).insert(appearance.clone());
+                        appearance::spawn_decorations(
+                            &mut cmds,
+                            copy,
+                            appearance,
+                            color,
+                            &asset_server,
+                            &glow_image,
+                            &mut meshes,
+                            &mut materials,
+                        );
+                    }
+
+                    cmds.entity(entity).remove::<Selected>();
+                }
+            }
+            SelectionEvent::Translate(offset) => {
+                for (.., mut trajectory, _, _, _, _) in q_selected.iter_mut() {
+                    trajectory.front_mut().expect("Trajectory empty").position += offset;
+                }
+            }
+            SelectionEvent::AddVelocity(delta_v) => {
+                for (.., mut trajectory, _, _, _, _) in q_selected.iter_mut() {
+                    trajectory.front_mut().expect("Trajectory empty").velocity += delta_v;
+                }
+            }
+            SelectionEvent::ScaleMass(factor) => {
+                for (_, _, mut mass, ..) in q_selected.iter_mut() {
+                    mass.0 *= factor;
+                }
+            }
+            SelectionEvent::SetTrajectoryVisibility(visible) => {
+                for (.., mut vis, _, _, _) in q_selected.iter_mut() {
+                    vis.0 = visible;
+                }
+                // only the drawing changes, the trajectories stay valid
+                continue;
+            }
+            SelectionEvent::FollowCentreOfMass => {
+                for entity in q_followed.iter() {
+                    cmds.entity(entity).remove::<Follow>();
+                }
+                for (entity, ..) in q_selected.iter() {
+                    cmds.entity(entity).insert(Follow);
+                }
+                continue;
+            }
+        }
+
+        clear_traj_evw.send(ClearTrajectories);
+    }
+}
+
+pub struct SelectionPlugin;
+
+impl Plugin for SelectionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<SelectionEvent>()
+            .add_systems(Update, apply_selection_events.in_set(SimSystemSet));
+    }
+}
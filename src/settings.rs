@@ -0,0 +1,155 @@
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use bevy::{asset::ron, prelude::*};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    input::{GamepadMap, InputMap},
+    sim::SimData,
+};
+
+const SETTINGS_FILE: &str = "settings.ron";
+
+/// Preferences applied to every simulation
+#[derive(Resource, Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Preferences {
+    pub prediction_horizon: f32,
+    /// Simulation time played back per second
+    pub time_scale: f32,
+}
+
+impl Default for Preferences {
+    fn default() -> Self {
+        let sim_data = SimData::default();
+        Self {
+            prediction_horizon: sim_data.prediction_horizon,
+            time_scale: sim_data.speed,
+        }
+    }
+}
+
+/// The contents of the settings file
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+struct Settings {
+    bindings: Option<InputMap>,
+    gamepad_bindings: Option<GamepadMap>,
+    preferences: Preferences,
+}
+
+#[non_exhaustive]
+#[derive(Debug, Error)]
+pub enum SettingsError {
+    /// An [IO](std::io) Error
+    #[error("Could not access settings file: {0}")]
+    Io(#[from] std::io::Error),
+    /// A [RON](ron) Error
+    #[error("Could not parse settings: {0}")]
+    RonSpannedError(#[from] ron::error::SpannedError),
+    #[error("Could not serialize settings: {0}")]
+    RonError(#[from] ron::Error),
+}
+
+fn config_dir() -> Option<PathBuf> {
+    if cfg!(target_os = "windows") {
+        env::var_os("APPDATA").map(PathBuf::from)
+    } else if cfg!(target_os = "macos") {
+        env::var_os("HOME").map(|home| PathBuf::from(home).join("Library/Application Support"))
+    } else {
+        env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+    }
+}
+
+pub fn settings_path() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join("nbody").join(SETTINGS_FILE))
+}
+
+fn read_settings(path: &Path) -> Result<Settings, SettingsError> {
+    let contents = fs::read_to_string(path)?;
+    Ok(ron::de::from_str(&contents)?)
+}
+
+fn write_settings(path: &Path, settings: &Settings) -> Result<(), SettingsError> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let contents = ron::ser::to_string_pretty(settings, ron::ser::PrettyConfig::default())?;
+    fs::write(path, contents)?;
+    Ok(())
+}
+
+fn load_settings(
+    mut input_map: ResMut<InputMap>,
+    mut gamepad_map: ResMut<GamepadMap>,
+    mut preferences: ResMut<Preferences>,
+) {
+    let Some(path) = settings_path().filter(|path| path.exists()) else {
+        return;
+    };
+
+    match read_settings(&path) {
+        Ok(settings) => {
+            // actions missing from the file keep their default binding
+            if let Some(bindings) = settings.bindings {
+                input_map.0.extend(bindings.0);
+            }
+            if let Some(bindings) = settings.gamepad_bindings {
+                gamepad_map.0.extend(bindings.0);
+            }
+            *preferences = settings.preferences;
+        }
+        Err(err) => warn!("Failed to load {}: {err}", path.display()),
+    }
+}
+
+fn apply_preferences(preferences: Res<Preferences>, mut sim_data: ResMut<SimData>) {
+    sim_data.prediction_horizon = preferences.prediction_horizon;
+    sim_data.speed = preferences.time_scale;
+}
+
+fn save_settings(
+    input_map: Res<InputMap>,
+    gamepad_map: Res<GamepadMap>,
+    preferences: Res<Preferences>,
+) {
+    let Some(path) = settings_path() else {
+        return;
+    };
+
+    let settings = Settings {
+        bindings: Some(input_map.clone()),
+        gamepad_bindings: Some(gamepad_map.clone()),
+        preferences: *preferences,
+    };
+    if let Err(err) = write_settings(&path, &settings) {
+        error!("Failed to save {}: {err}", path.display());
+    }
+}
+
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<InputMap>()
+            .init_resource::<GamepadMap>()
+            .init_resource::<Preferences>()
+            .add_systems(PreStartup, load_settings)
+            .add_systems(Startup, apply_preferences)
+            .add_systems(
+                Last,
+                save_settings.run_if(
+                    (resource_changed::<InputMap>
+                        .or_else(resource_changed::<GamepadMap>)
+                        .or_else(resource_changed::<Preferences>))
+                    .and_then(not(resource_added::<InputMap>)),
+                ),
+            );
+    }
+}
@@ -0,0 +1,238 @@
+use bevy::{
+    asset::LoadState,
+    prelude::*,
+    render::{
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+    },
+    sprite::{MaterialMesh2dBundle, Mesh2dHandle},
+};
+use serde::{Deserialize, Serialize};
+
+const GLOW_RESOLUTION: u32 = 64;
+
+/// Emissive halo around a body, e.g. for stars
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Glow {
+    /// Colour of the glow instead of the body's colour
+    pub colour: Option<Color>,
+    /// In multiples of the body's radius
+    pub radius: f32,
+    /// Opacity at the centre of the glow
+    pub intensity: f32,
+}
+
+impl Default for Glow {
+    fn default() -> Self {
+        Self {
+            colour: None,
+            radius: 3.0,
+            intensity: 0.8,
+        }
+    }
+}
+
+/// A flat ring system around a body
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Rings {
+    /// In multiples of the body's radius
+    pub inner_radius: f32,
+    /// In multiples of the body's radius
+    pub outer_radius: f32,
+    pub colour: Color,
+}
+
+/// What is drawn on and around a body besides its coloured circle, kept so that its
+/// [`Decoration`]s can be spawned again for copies of the body
+#[derive(Component, Clone, Debug, Default, PartialEq)]
+pub struct Appearance {
+    /// Path of an image drawn on the body, relative to the assets folder
+    pub texture: Option<String>,
+    pub glow: Option<Glow>,
+    pub rings: Option<Rings>,
+}
+
+/// What a [`Decoration`] draws
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum DecorationKind {
+    Texture,
+    Glow,
+    Rings,
+}
+
+impl DecorationKind {
+    fn z_offset(&self) -> f32 {
+        match self {
+            DecorationKind::Texture => 0.1,
+            DecorationKind::Glow => -0.2,
+            DecorationKind::Rings => -0.1,
+        }
+    }
+}
+
+/// Mesh drawn on top of or around `body`, following its position and radius
+#[derive(Component)]
+pub(super) struct Decoration {
+    body: Entity,
+    kind: DecorationKind,
+}
+
+/// Image texture of a body that is only shown once it's loaded, so the body keeps its plain colour
+/// if the texture is missing
+#[derive(Component)]
+pub(super) struct BodyTexture(Handle<Image>);
+
+/// Radial gradient shared by all glows
+#[derive(Resource)]
+pub struct GlowImage(Handle<Image>);
+
+impl FromWorld for GlowImage {
+    fn from_world(world: &mut World) -> Self {
+        let centre = (GLOW_RESOLUTION as f32 - 1.0) / 2.0;
+        let data = (0..GLOW_RESOLUTION * GLOW_RESOLUTION)
+            .flat_map(|i| {
+                let pixel = Vec2::new((i % GLOW_RESOLUTION) as f32, (i / GLOW_RESOLUTION) as f32);
+                let distance = (pixel.distance(Vec2::splat(centre)) / centre).min(1.0);
+                [255, 255, 255, ((1.0 - distance).powi(2) * 255.0) as u8]
+            })
+            .collect();
+
+        let image = Image::new(
+            Extent3d {
+                width: GLOW_RESOLUTION,
+                height: GLOW_RESOLUTION,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            data,
+            TextureFormat::Rgba8UnormSrgb,
+            RenderAssetUsages::RENDER_WORLD,
+        );
+        Self(world.resource_mut::<Assets<Image>>().add(image))
+    }
+}
+
+/// Spawns the texture, glow and rings of a body with the colour `body_colour`
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn spawn_decorations(
+    cmds: &mut Commands,
+    body: Entity,
+    appearance: &Appearance,
+    body_colour: Color,
+    asset_server: &AssetServer,
+    glow_image: &GlowImage,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<ColorMaterial>,
+) {
+    // the body's mesh is a circle with a radius of 0.5 scaled by the body's radius
+    if let Some(path) = &appearance.texture {
+        cmds.spawn((
+            MaterialMesh2dBundle {
+                mesh: Mesh2dHandle(meshes.add(Circle::default())),
+                material: materials.add(Color::WHITE),
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            Decoration {
+                body,
+                kind: DecorationKind::Texture,
+            },
+            BodyTexture(asset_server.load(path.clone())),
+        ));
+    }
+
+    let mut spawn = |kind: DecorationKind, mesh: Mesh, material: ColorMaterial| {
+        cmds.spawn((
+            MaterialMesh2dBundle {
+                mesh: Mesh2dHandle(meshes.add(mesh)),
+                material: materials.add(material),
+                ..default()
+            },
+            Decoration { body, kind },
+        ));
+    };
+
+    if let Some(glow) = &appearance.glow {
+        let colour = glow.colour.unwrap_or(body_colour);
+        spawn(
+            DecorationKind::Glow,
+            Circle::new(0.5 * glow.radius).into(),
+            ColorMaterial {
+                color: colour.with_alpha(glow.intensity),
+                texture: Some(glow_image.0.clone()),
+            },
+        );
+    }
+
+    if let Some(rings) = &appearance.rings {
+        spawn(
+            DecorationKind::Rings,
+            Annulus::new(0.5 * rings.inner_radius, 0.5 * rings.outer_radius).into(),
+            ColorMaterial::from(rings.colour),
+        );
+    }
+}
+
+/// Applies body textures once they're loaded and drops the ones that failed to load
+pub(super) fn apply_body_textures(
+    mut image_events: EventReader<AssetEvent<Image>>,
+    mut textures: Query<(
+        Entity,
+        &BodyTexture,
+        &Handle<ColorMaterial>,
+        &mut Visibility,
+    )>,
+    asset_server: Res<AssetServer>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut cmds: Commands,
+) {
+    let modified = image_events
+        .read()
+        .filter_map(|ev| match ev {
+            AssetEvent::Modified { id } => Some(*id),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    for (entity, BodyTexture(image), material_handle, mut visibility) in textures.iter_mut() {
+        match asset_server.load_state(image) {
+            LoadState::Loaded => {
+                let Some(material) = materials.get(material_handle) else {
+                    continue;
+                };
+                // touching the material rebuilds it with the reloaded image
+                if material.texture.is_none() || modified.contains(&image.id()) {
+                    if let Some(material) = materials.get_mut(material_handle) {
+                        material.texture = Some(image.clone());
+                    }
+                    *visibility = Visibility::Inherited;
+                }
+            }
+            LoadState::Failed(err) => {
+                warn!("Failed to load body texture, using its colour instead: {err}");
+                cmds.entity(entity).despawn();
+            }
+            LoadState::NotLoaded | LoadState::Loading => (),
+        }
+    }
+}
+
+/// Moves the decorations to their bodies and despawns the ones of removed bodies
+pub(super) fn follow_bodies(
+    mut decorations: Query<(Entity, &Decoration, &mut Transform)>,
+    bodies: Query<&Transform, Without<Decoration>>,
+    mut cmds: Commands,
+) {
+    for (entity, decoration, mut transform) in decorations.iter_mut() {
+        let Ok(body_transform) = bodies.get(decoration.body) else {
+            cmds.entity(entity).despawn();
+            continue;
+        };
+
+        transform.translation = body_transform
+            .translation
+            .with_z(body_transform.translation.z + decoration.kind.z_offset());
+        transform.scale = body_transform.scale.with_z(1.0);
+    }
+}
@@ -0,0 +1,314 @@
+use core::f32::consts::TAU;
+use std::io::{self, Write};
+
+use bevy::{prelude::*, utils::HashMap};
+
+use super::{orbit, ClearTrajectories, Mass, Name, Radius, SimClock, SimData, Trajectory};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SimEventKind {
+    Periapsis,
+    Apoapsis,
+    CloseApproach,
+    /// Positive specific energy beyond [`EventDetector::escape_distance`]
+    Escape,
+    Collision,
+    /// A full revolution around the parent body
+    OrbitCompleted,
+}
+
+impl SimEventKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            SimEventKind::Periapsis => "Periapsis",
+            SimEventKind::Apoapsis => "Apoapsis",
+            SimEventKind::CloseApproach => "Close approach",
+            SimEventKind::Escape => "Escape",
+            SimEventKind::Collision => "Collision",
+            SimEventKind::OrbitCompleted => "Orbit completed",
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct SimEvent {
+    /// The step of the [`SimClock`] the event happens at
+    pub step: u64,
+    pub kind: SimEventKind,
+    pub body: Entity,
+    pub body_name: String,
+    /// Name of the parent body for orbital events, the other body for close approaches and
+    /// collisions
+    pub other_name: String,
+    /// Distance between the two bodies
+    pub distance: f32,
+}
+
+impl SimEvent {
+    pub fn time(&self) -> f64 {
+        SimClock::time_at(self.step)
+    }
+}
+
+/// Events detected while computing the trajectories, in the order they happen.
+/// Events after the current step are predictions and get dropped when the trajectories are cleared.
+#[derive(Resource, Default)]
+pub struct EventLog(pub Vec<SimEvent>);
+
+impl EventLog {
+    /// Events that already happened
+    pub fn past(&self, clock: &SimClock) -> &[SimEvent] {
+        let len = self.0.partition_point(|ev| ev.step <= clock.steps);
+        &self.0[..len]
+    }
+
+    pub fn write_csv(&self, clock: &SimClock, mut writer: impl Write) -> io::Result<()> {
+        writeln!(writer, "time,event,body,other,distance")?;
+        for ev in self.past(clock) {
+            writeln!(
+                writer,
+                "{},{},{},{},{}",
+                ev.time(),
+                ev.kind.label(),
+                csv_escape(&ev.body_name),
+                csv_escape(&ev.other_name),
+                ev.distance,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+fn csv_escape(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// State of a body at the last checked step, relative to its parent
+struct BodyTracker {
+    parent: Entity,
+    rel_pos: Vec3,
+    /// Direction of the angular momentum when the tracking started, the swept angle is signed
+    /// around it
+    normal: Vec3,
+    radial_vel: f32,
+    swept_angle: f32,
+    escaped: bool,
+}
+
+/// State of a pair of bodies at the last checked step
+#[derive(Default)]
+struct PairTracker {
+    distance: f32,
+    approaching: bool,
+    colliding: bool,
+}
+
+#[derive(Resource)]
+pub struct EventDetector {
+    pub enabled: bool,
+    /// Closest approaches below this distance are logged
+    pub close_approach_distance: f32,
+    /// Unbound bodies are logged as escaped beyond this distance from their parent
+    pub escape_distance: f32,
+    next_step: u64,
+    bodies: HashMap<Entity, BodyTracker>,
+    pairs: HashMap<(Entity, Entity), PairTracker>,
+}
+
+impl Default for EventDetector {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            close_approach_distance: 5.0,
+            escape_distance: 500.0,
+            next_step: 0,
+            bodies: HashMap::new(),
+            pairs: HashMap::new(),
+        }
+    }
+}
+
+impl EventDetector {
+    fn reset(&mut self, step: u64) {
+        self.next_step = step;
+        self.bodies.clear();
+        self.pairs.clear();
+    }
+}
+
+/// Checks the trajectory steps computed since the last run for events
+pub(super) fn detect_events(
+    mut clear_ev: EventReader<ClearTrajectories>,
+    mut detector: ResMut<EventDetector>,
+    mut log: ResMut<EventLog>,
+    sim: Res<SimData>,
+    clock: Res<SimClock>,
+    bodies: Query<(Entity, &Trajectory, &Mass, &Radius, &Name)>,
+) {
+    let detector = &mut *detector;
+
+    if clear_ev.read().count() > 0 || detector.next_step < clock.steps {
+        // predictions past the current step are no longer valid
+        let past_len = log.past(&clock).len();
+        log.0.truncate(past_len);
+        detector.reset(clock.steps);
+    }
+
+    // despawned bodies are never checked again
+    detector
+        .bodies
+        .retain(|entity, tracker| bodies.contains(*entity) && bodies.contains(tracker.parent));
+    detector
+        .pairs
+        .retain(|(a, b), _| bodies.contains(*a) && bodies.contains(*b));
+
+    let len = bodies.iter().map(|(_, traj, ..)| traj.0.len()).min();
+    let first = (detector.next_step - clock.steps) as usize;
+
+    if !detector.enabled {
+        detector.reset(clock.steps + len.unwrap_or(0) as u64);
+        return;
+    }
+
+    for i in first..len.unwrap_or(0) {
+        let step = clock.steps + i as u64;
+        let at_step = bodies
+            .iter()
+            .map(|(entity, traj, mass, radius, name)| (entity, traj.0[i], mass.0, radius.0, name))
+            .collect::<Vec<_>>();
+        let parents = orbit::dominant_attractors(
+            &at_step
+                .iter()
+                .map(|(_, current, mass, ..)| (current.position3(), *mass))
+                .collect::<Vec<_>>(),
+        );
+
+        let mut event = |kind, body: usize, other: usize, distance| {
+            let (body_entity, .., body_name) = at_step[body];
+            let (.., other_name) = at_step[other];
+            log.0.push(SimEvent {
+                step,
+                kind,
+                body: body_entity,
+                body_name: body_name.0.clone(),
+                other_name: other_name.0.clone(),
+                distance,
+            });
+        };
+
+        for (body, parent) in parents.iter().enumerate() {
+            let (entity, current, mass, ..) = at_step[body];
+            let Some(parent) = *parent else {
+                detector.bodies.remove(&entity);
+                continue;
+            };
+            let (parent_entity, parent_state, parent_mass, ..) = at_step[parent];
+
+            let rel_pos = current.position3() - parent_state.position3();
+            let rel_vel = current.velocity3() - parent_state.velocity3();
+            let radial_vel = rel_pos.dot(rel_vel);
+            let mu = sim.gravitational_const * (mass + parent_mass);
+            let unbound = orbit::specific_energy(rel_pos, rel_vel, mu) > 0.0;
+
+            match detector.bodies.get_mut(&entity) {
+                Some(tracker) if tracker.parent == parent_entity => {
+                    if tracker.radial_vel < 0.0 && radial_vel >= 0.0 {
+                        event(SimEventKind::Periapsis, body, parent, rel_pos.length());
+                    } else if tracker.radial_vel > 0.0 && radial_vel <= 0.0 {
+                        event(SimEventKind::Apoapsis, body, parent, rel_pos.length());
+                    }
+
+                    let cross = tracker.rel_pos.cross(rel_pos);
+                    tracker.swept_angle += cross
+                        .dot(tracker.normal)
+                        .atan2(tracker.rel_pos.dot(rel_pos));
+                    if tracker.swept_angle.abs() >= TAU {
+                        tracker.swept_angle -= TAU * tracker.swept_angle.signum();
+                        event(SimEventKind::OrbitCompleted, body, parent, rel_pos.length());
+                    }
+
+                    if unbound && !tracker.escaped && rel_pos.length() > detector.escape_distance {
+                        tracker.escaped = true;
+                        event(SimEventKind::Escape, body, parent, rel_pos.length());
+                    } else if !unbound {
+                        tracker.escaped = false;
+                    }
+
+                    tracker.rel_pos = rel_pos;
+                    tracker.radial_vel = radial_vel;
+                }
+                // a new parent starts a new orbit
+                _ => {
+                    detector.bodies.insert(
+                        entity,
+                        BodyTracker {
+                            parent: parent_entity,
+                            rel_pos,
+                            normal: rel_pos.cross(rel_vel).try_normalize().unwrap_or(Vec3::Z),
+                            radial_vel,
+                            swept_angle: 0.0,
+                            escaped: unbound && rel_pos.length() > detector.escape_distance,
+                        },
+                    );
+                }
+            }
+        }
+
+        for a in 0..at_step.len() {
+            for b in a + 1..at_step.len() {
+                let (a_entity, a_state, _, a_radius, _) = at_step[a];
+                let (b_entity, b_state, _, b_radius, _) = at_step[b];
+                let distance = a_state.position3().distance(b_state.position3());
+                let colliding = distance < a_radius + b_radius;
+                let close_approach_distance = detector.close_approach_distance;
+
+                let key = if a_entity < b_entity {
+                    (a_entity, b_entity)
+                } else {
+                    (b_entity, a_entity)
+                };
+
+                if let Some(tracker) = detector.pairs.get_mut(&key) {
+                    let approaching = distance < tracker.distance;
+
+                    if colliding && !tracker.colliding {
+                        event(SimEventKind::Collision, a, b, distance);
+                    }
+                    // the previous step was the closest one
+                    if tracker.approaching
+                        && !approaching
+                        && tracker.distance < close_approach_distance
+                    {
+                        event(SimEventKind::CloseApproach, a, b, tracker.distance);
+                    }
+
+                    *tracker = PairTracker {
+                        distance,
+                        approaching,
+                        colliding,
+                    };
+                } else {
+                    detector.pairs.insert(
+                        key,
+                        PairTracker {
+                            distance,
+                            colliding,
+                            ..default()
+                        },
+                    );
+                }
+            }
+        }
+    }
+
+    detector.next_step = clock.steps + len.unwrap_or(0) as u64;
+}
+
+pub(super) fn reset_events(mut detector: ResMut<EventDetector>, mut log: ResMut<EventLog>) {
+    detector.reset(0);
+    log.0.clear();
+}
@@ -0,0 +1,268 @@
+use std::{
+    collections::HashSet,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use bevy::{asset::ron, prelude::*};
+use thiserror::Error;
+
+use super::{
+    appearance::Appearance,
+    forces::{BodyForces, ForceSources},
+    manoeuvre::Manoeuvres,
+    trajectory::TrajectoryStyle,
+    Mass, Name, Radius, SimClock, SimData, Tag, Trajectory,
+};
+use crate::assets::{body::Body, system::System};
+
+/// Writes every body to a `*.body.ron` file in `folder` as it is now, and the constants of the
+/// simulation to a `*.system.ron` file, so that the folder can be loaded as a system again
+#[derive(Event)]
+pub struct ExportBodies {
+    pub folder: PathBuf,
+}
+
+/// Result of the last [`ExportBodies`], shown in the UI
+#[derive(Resource, Default)]
+pub struct ExportStatus(pub Option<String>);
+
+#[non_exhaustive]
+#[derive(Debug, Error)]
+pub enum ExportError {
+    /// An [IO](std::io) Error
+    #[error("Could not write file: {0}")]
+    Io(#[from] std::io::Error),
+    /// A [RON](ron) Error
+    #[error("Could not serialize: {0}")]
+    RonError(#[from] ron::Error),
+}
+
+fn file_stem(name: &str, taken: &mut HashSet<String>) -> String {
+    let mut stem = name
+        .trim()
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '_'
+            }
+        })
+        .collect::<String>();
+    if stem.is_empty() {
+        stem = "body".to_string();
+    }
+
+    let mut unique = stem.clone();
+    let mut count = 1;
+    while !taken.insert(unique.clone()) {
+        count += 1;
+        unique = format!("{stem}_{count}");
+    }
+    unique
+}
+
+fn write_body(folder: &Path, stem: &str, body: &Body) -> Result<(), ExportError> {
+    let contents = ron::ser::to_string_pretty(body, ron::ser::PrettyConfig::default())?;
+    fs::write(folder.join(format!("{stem}.body.ron")), contents)?;
+    Ok(())
+}
+
+fn system(folder: &Path, sim_data: &SimData) -> System {
+    let name = folder.file_name().map_or("export".to_string(), |name| {
+        name.to_string_lossy().into_owned()
+    });
+
+    System {
+        folder: name.clone(),
+        display_name: name,
+        gravitational_const: sim_data.gravitational_const,
+        speed_of_light: sim_data.post_newtonian.then_some(sim_data.speed_of_light),
+    }
+}
+
+fn write_system(folder: &Path, system: &System) -> Result<(), ExportError> {
+    let contents = ron::ser::to_string_pretty(system, ron::ser::PrettyConfig::default())?;
+    let stem = file_stem(&system.folder, &mut HashSet::new());
+    fs::write(folder.join(format!("{stem}.system.ron")), contents)?;
+    Ok(())
+}
+
+/// Moves the burns of a body to the time of a system loaded from the export, which starts at
+/// zero again, and drops the ones that are already over
+fn rebase_burns(forces: &mut BodyForces, manoeuvres: &Manoeuvres, elapsed: f64) -> Manoeuvres {
+    if let Some(thrust) = &mut forces.thrust {
+        thrust.burns.retain_mut(|burn| {
+            burn.start -= elapsed as f32;
+            burn.start + burn.duration > 0.0
+        });
+    }
+
+    Manoeuvres(
+        manoeuvres
+            .0
+            .iter()
+            .filter(|node| node.time >= elapsed)
+            .map(|node| {
+                let mut node = node.clone();
+                node.time -= elapsed;
+                node
+            })
+            .collect(),
+    )
+}
+
+#[allow(clippy::type_complexity)]
+pub(super) fn export_bodies(
+    mut export_evr: EventReader<ExportBodies>,
+    mut status: ResMut<ExportStatus>,
+    clock: Res<SimClock>,
+    sim_data: Res<SimData>,
+    bodies: Query<(
+        &Name,
+        Option<&Tag>,
+        &Mass,
+        &Radius,
+        &Trajectory,
+        &Handle<ColorMaterial>,
+        Option<&TrajectoryStyle>,
+        Option<&Appearance>,
+        ForceSources,
+        Option<&Manoeuvres>,
+    )>,
+    materials: Res<Assets<ColorMaterial>>,
+) {
+    for ExportBodies { folder } in export_evr.read() {
+        let mut taken = HashSet::new();
+
+        let result = fs::create_dir_all(folder)
+            .map_err(ExportError::from)
+            .and_then(|()| {
+                for (
+                    name,
+                    tag,
+                    mass,
+                    radius,
+                    trajectory,
+                    material,
+                    style,
+                    appearance,
+                    sources,
+                    manoeuvres,
+                ) in bodies.iter()
+                {
+                    let Some(current) = trajectory.front() else {
+                        continue;
+                    };
+                    let appearance = appearance.cloned().unwrap_or_default();
+                    let mut forces = BodyForces::from_sources(&sources);
+                    let manoeuvres = rebase_burns(
+                        &mut forces,
+                        manoeuvres.unwrap_or(&Manoeuvres::default()),
+                        clock.elapsed(),
+                    );
+
+                    let body = Body {
+                        initial_pos: current.position,
+                        velocity: current.velocity,
+                        z: current.z,
+                        velocity_z: current.velocity_z,
+                        inclination: 0.0,
+                        mass: mass.0,
+                        radius: radius.0,
+                        color: materials
+                            .get(material)
+                            .map_or(Color::WHITE, |material| material.color),
+                        name: name.0.clone(),
+                        tag: tag.map(|tag| tag.0.clone()),
+                        trajectory_style: style.cloned().unwrap_or_default(),
+                        texture: appearance.texture,
+                        glow: appearance.glow,
+                        rings: appearance.rings,
+                        forces,
+                        manoeuvres,
+                    };
+                    write_body(folder, &file_stem(&name.0, &mut taken), &body)?;
+                }
+                write_system(folder, &system(folder, &sim_data))
+            });
+
+        status.0 = Some(match result {
+            Ok(()) => format!("Exported {} bodies to {}", taken.len(), folder.display()),
+            Err(err) => {
+                error!("Failed to export bodies to {}: {err}", folder.display());
+                format!("Export failed: {err}")
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sim::{manoeuvre::ManoeuvreNode, trajectory::FadeCurve};
+
+    #[test]
+    fn style_and_manoeuvres_round_trip() {
+        let body = Body {
+            initial_pos: Vec2::new(1.0, 2.0),
+            velocity: Vec2::new(0.0, 3.0),
+            z: 0.0,
+            velocity_z: 0.0,
+            inclination: 0.0,
+            mass: 10.0,
+            radius: 0.5,
+            color: Color::srgb(0.2, 0.4, 0.6),
+            name: "Probe".to_string(),
+            tag: Some("Spacecraft".to_string()),
+            trajectory_style: TrajectoryStyle {
+                width: 3.5,
+                colour: Some(Color::srgb(1.0, 0.5, 0.0)),
+                dashed: true,
+                fade: FadeCurve::Quadratic,
+                opacity: 0.4,
+                colour_by_speed: true,
+            },
+            texture: None,
+            glow: None,
+            rings: None,
+            forces: BodyForces::default(),
+            manoeuvres: Manoeuvres(vec![ManoeuvreNode {
+                time: 1.5,
+                prograde: 0.25,
+                radial: -0.1,
+            }]),
+        };
+
+        let contents =
+            ron::ser::to_string_pretty(&body, ron::ser::PrettyConfig::default()).unwrap();
+        let loaded = ron::de::from_str::<Body>(&contents).unwrap();
+
+        assert_eq!(loaded.trajectory_style, body.trajectory_style);
+        assert_eq!(loaded.manoeuvres, body.manoeuvres);
+        assert_eq!(loaded.name, body.name);
+        assert_eq!(loaded.tag, body.tag);
+    }
+
+    #[test]
+    fn constants_round_trip() {
+        let sim_data = SimData {
+            gravitational_const: 6.6743e-11,
+            post_newtonian: true,
+            speed_of_light: 3.0e5,
+            ..default()
+        };
+
+        let system = system(Path::new("exports/binary"), &sim_data);
+        assert_eq!(system.folder, "binary");
+        let contents =
+            ron::ser::to_string_pretty(&system, ron::ser::PrettyConfig::default()).unwrap();
+        let mut loaded = SimData::default();
+        loaded.load_constants(&ron::de::from_str::<System>(&contents).unwrap());
+
+        assert_eq!(loaded.gravitational_const, sim_data.gravitational_const);
+        assert!(loaded.post_newtonian);
+        assert_eq!(loaded.speed_of_light, sim_data.speed_of_light);
+    }
+}
@@ -0,0 +1,216 @@
+use bevy::{
+    ecs::{query::QueryData, system::EntityCommands},
+    prelude::*,
+};
+use serde::{Deserialize, Serialize};
+
+use super::SimSnapshot;
+
+/// Force pushing a body all the time, plus scheduled burns on top of it
+#[derive(Component, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Thrust {
+    /// Force applied outside of and during the burns
+    pub constant: Vec3,
+    pub burns: Vec<Burn>,
+    /// Whether the forces are given as prograde, lateral and normal components along the body's
+    /// velocity instead of along the inertial axes
+    pub relative_to_velocity: bool,
+}
+
+/// A force applied from `start` for `duration`, in simulation time
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Burn {
+    pub start: f32,
+    pub duration: f32,
+    pub force: Vec3,
+}
+
+impl Thrust {
+    /// Force in inertial axes at the given simulation time
+    pub fn force_at(&self, time: f32, velocity: Vec3) -> Vec3 {
+        let force = self.constant
+            + self
+                .burns
+                .iter()
+                .filter(|burn| (burn.start..burn.start + burn.duration).contains(&time))
+                .map(|burn| burn.force)
+                .sum::<Vec3>();
+
+        if !self.relative_to_velocity || force == Vec3::ZERO {
+            return force;
+        }
+
+        let prograde = velocity.normalize_or_zero();
+        let lateral = Vec3::Z.cross(prograde).normalize_or_zero();
+        if lateral == Vec3::ZERO {
+            // no direction to go by when standing still or moving straight out of the plane
+            return force;
+        }
+        let normal = prograde.cross(lateral);
+
+        prograde * force.x + lateral * force.y + normal * force.z
+    }
+}
+
+/// Makes a body slow down while moving through the [`Atmosphere`] of another body
+#[derive(Component, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Drag {
+    /// Drag coefficient times the cross-sectional area
+    pub coefficient: f32,
+}
+
+impl Default for Drag {
+    fn default() -> Self {
+        Self { coefficient: 1.0 }
+    }
+}
+
+/// Atmosphere slowing down bodies with [`Drag`], thinning out exponentially above the surface
+#[derive(Component, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Atmosphere {
+    /// Density at the body's radius
+    pub surface_density: f32,
+    /// Altitude over which the density drops by a factor of e
+    pub scale_height: f32,
+}
+
+impl Default for Atmosphere {
+    fn default() -> Self {
+        Self {
+            surface_density: 1.0,
+            scale_height: 1.0,
+        }
+    }
+}
+
+impl Atmosphere {
+    pub fn density_at(&self, altitude: f32) -> f32 {
+        self.surface_density * (-altitude.max(0.0) / self.scale_height).exp()
+    }
+}
+
+/// Makes a body get pushed away from every [`Star`]
+#[derive(Component, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RadiationPressure {
+    /// Area facing the star, scaled by how much of the light is reflected
+    pub area: f32,
+}
+
+impl Default for RadiationPressure {
+    fn default() -> Self {
+        Self { area: 1.0 }
+    }
+}
+
+/// A body whose light pushes bodies with [`RadiationPressure`] away
+#[derive(Component, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Star {
+    /// Radiation pressure at unit distance, falling off with the square of the distance
+    pub pressure: f32,
+}
+
+impl Default for Star {
+    fn default() -> Self {
+        Self { pressure: 1.0 }
+    }
+}
+
+/// The components of a body that cause or feel forces other than gravity
+#[derive(QueryData)]
+pub struct ForceSources {
+    pub thrust: Option<&'static Thrust>,
+    pub drag: Option<&'static Drag>,
+    pub atmosphere: Option<&'static Atmosphere>,
+    pub radiation_pressure: Option<&'static RadiationPressure>,
+    pub star: Option<&'static Star>,
+}
+
+impl ForceSourcesItem<'_> {
+    fn is_empty(&self) -> bool {
+        self.thrust.is_none()
+            && self.drag.is_none()
+            && self.atmosphere.is_none()
+            && self.radiation_pressure.is_none()
+            && self.star.is_none()
+    }
+
+    /// Acceleration of a body with these sources from its own thrust
+    pub fn thrust_acceleration(&self, state: SimSnapshot, mass: f32, time: f32) -> Vec3 {
+        match self.thrust {
+            Some(thrust) if mass > 0.0 => thrust.force_at(time, state.velocity3()) / mass,
+            _ => Vec3::ZERO,
+        }
+    }
+
+    /// Acceleration of a body with these sources caused by the atmosphere and the light of
+    /// `other`
+    pub fn acceleration_from(
+        &self,
+        (state, mass): (SimSnapshot, f32),
+        (other, other_radius, other_sources): (SimSnapshot, f32, &ForceSourcesItem),
+    ) -> Vec3 {
+        if mass <= 0.0 || self.is_empty() {
+            return Vec3::ZERO;
+        }
+
+        let offset = state.position3() - other.position3();
+        let distance = offset.length();
+        if distance == 0.0 {
+            return Vec3::ZERO;
+        }
+        let mut accel = Vec3::ZERO;
+
+        if let (Some(drag), Some(atmosphere)) = (self.drag, other_sources.atmosphere) {
+            let rel_vel = state.velocity3() - other.velocity3();
+            let density = atmosphere.density_at(distance - other_radius);
+            accel -= 0.5 * density * drag.coefficient * rel_vel.length() * rel_vel / mass;
+        }
+
+        if let (Some(radiation), Some(star)) = (self.radiation_pressure, other_sources.star) {
+            accel += offset / distance * star.pressure * radiation.area / (distance.powi(2) * mass);
+        }
+
+        accel
+    }
+}
+
+/// Owned copies of the [`ForceSources`] of a body, as configured in body files and edited in
+/// the inspector
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BodyForces {
+    pub thrust: Option<Thrust>,
+    pub drag: Option<Drag>,
+    pub atmosphere: Option<Atmosphere>,
+    pub radiation_pressure: Option<RadiationPressure>,
+    pub star: Option<Star>,
+}
+
+impl BodyForces {
+    pub fn from_sources(sources: &ForceSourcesItem) -> Self {
+        Self {
+            thrust: sources.thrust.cloned(),
+            drag: sources.drag.cloned(),
+            atmosphere: sources.atmosphere.cloned(),
+            radiation_pressure: sources.radiation_pressure.cloned(),
+            star: sources.star.cloned(),
+        }
+    }
+
+    /// Inserts the configured components into the entity and removes the others
+    pub fn apply(self, entity: &mut EntityCommands) {
+        fn set<T: Component>(entity: &mut EntityCommands, component: Option<T>) {
+            match component {
+                Some(component) => entity.insert(component),
+                None => entity.remove::<T>(),
+            };
+        }
+
+        set(entity, self.thrust);
+        set(entity, self.drag);
+        set(entity, self.atmosphere);
+        set(entity, self.radiation_pressure);
+        set(entity, self.star);
+    }
+}
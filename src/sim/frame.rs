@@ -0,0 +1,251 @@
+use std::ops::Range;
+
+use bevy::prelude::*;
+
+use super::{centre_of_mass, Mass, SimData, SimSnapshot, Trajectory};
+
+/// The frame of reference that bodies, trajectories and hover labels are shown in.
+/// The simulation itself always runs in the inertial frame.
+#[derive(Resource, Default, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ReferenceFrame {
+    #[default]
+    Inertial,
+    /// Centred on the barycentre of all bodies
+    Barycentric,
+    /// Centred on a single body
+    Body(Entity),
+    /// Centred on the barycentre of two bodies and rotating with them, so that the secondary
+    /// always stays on the positive x axis as seen from the primary
+    CoRotating { primary: Entity, secondary: Entity },
+}
+
+impl ReferenceFrame {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ReferenceFrame::Inertial => "Inertial",
+            ReferenceFrame::Barycentric => "Barycentric",
+            ReferenceFrame::Body(_) => "Body-centred",
+            ReferenceFrame::CoRotating { .. } => "Co-rotating",
+        }
+    }
+
+    /// Number of steps the frame can be computed for, which is limited by the trajectories of the
+    /// bodies it depends on. Returns `None` if one of them is missing.
+    pub fn available_steps(&self, bodies: &[(Entity, &Trajectory, f32)]) -> Option<usize> {
+        let len_of = |entity: Entity| {
+            bodies
+                .iter()
+                .find(|(e, ..)| *e == entity)
+                .map(|(_, traj, _)| traj.0.len())
+        };
+
+        match *self {
+            ReferenceFrame::Inertial | ReferenceFrame::Barycentric => Some(
+                bodies
+                    .iter()
+                    .map(|(_, traj, _)| traj.0.len())
+                    .max()
+                    .unwrap_or(0),
+            ),
+            ReferenceFrame::Body(entity) => len_of(entity),
+            ReferenceFrame::CoRotating { primary, secondary } => {
+                Some(len_of(primary)?.min(len_of(secondary)?))
+            }
+        }
+    }
+
+    /// Computes the frame for the given steps of the trajectories, counted from their fronts.
+    /// Returns `None` if a body the frame depends on is missing or its trajectory is too short.
+    pub fn snapshots(
+        &self,
+        bodies: &[(Entity, &Trajectory, f32)],
+        steps: Range<usize>,
+    ) -> Option<Vec<FrameSnapshot>> {
+        let find = |entity: Entity| {
+            bodies
+                .iter()
+                .find(|(e, ..)| *e == entity)
+                .map(|(_, traj, mass)| (*traj, *mass))
+        };
+
+        match *self {
+            ReferenceFrame::Inertial => Some(vec![FrameSnapshot::default(); steps.len()]),
+            ReferenceFrame::Barycentric => Some(
+                steps
+                    .map(|i| {
+                        let at_step = bodies
+                            .iter()
+                            .filter_map(|(_, traj, mass)| traj.0.get(i).map(|s| (*s, *mass)))
+                            .collect::<Vec<_>>();
+
+                        FrameSnapshot {
+                            origin: centre_of_mass(at_step.iter().map(|(s, m)| (s.position, *m))),
+                            velocity: centre_of_mass(at_step.iter().map(|(s, m)| (s.velocity, *m))),
+                            origin_z: centre_of_mass_z(at_step.iter().map(|(s, m)| (s.z, *m))),
+                            ..default()
+                        }
+                    })
+                    .collect(),
+            ),
+            ReferenceFrame::Body(entity) => {
+                let (traj, _) = find(entity)?;
+
+                steps
+                    .map(|i| {
+                        traj.0.get(i).map(|s| FrameSnapshot {
+                            origin: s.position,
+                            velocity: s.velocity,
+                            origin_z: s.z,
+                            ..default()
+                        })
+                    })
+                    .collect()
+            }
+            ReferenceFrame::CoRotating { primary, secondary } => {
+                let (primary_traj, primary_mass) = find(primary)?;
+                let (secondary_traj, secondary_mass) = find(secondary)?;
+
+                steps
+                    .map(|i| {
+                        let p = primary_traj.0.get(i)?;
+                        let s = secondary_traj.0.get(i)?;
+                        Some(FrameSnapshot::co_rotating(
+                            (*p, primary_mass),
+                            (*s, secondary_mass),
+                        ))
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+fn centre_of_mass_z(bodies: impl IntoIterator<Item = (f32, f32)>) -> f32 {
+    centre_of_mass(
+        bodies
+            .into_iter()
+            .map(|(z, mass)| (Vec2::new(z, 0.0), mass)),
+    )
+    .x
+}
+
+/// Position, velocity and rotation of a [`ReferenceFrame`] at a single step of the simulation
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct FrameSnapshot {
+    pub origin: Vec2,
+    pub velocity: Vec2,
+    pub angle: f32,
+    pub angular_velocity: f32,
+    /// Out-of-plane position of the origin, the frame never tilts
+    pub origin_z: f32,
+}
+
+impl FrameSnapshot {
+    /// The frame centred on the barycentre of the two bodies, with the secondary on the positive
+    /// x axis as seen from the primary
+    pub fn co_rotating(
+        (primary, primary_mass): (SimSnapshot, f32),
+        (secondary, secondary_mass): (SimSnapshot, f32),
+    ) -> Self {
+        let bodies = [(primary, primary_mass), (secondary, secondary_mass)];
+        let separation = secondary.position - primary.position;
+        let relative_vel = secondary.velocity - primary.velocity;
+
+        if separation == Vec2::ZERO {
+            return Self::default();
+        }
+
+        Self {
+            origin: centre_of_mass(bodies.map(|(s, m)| (s.position, m))),
+            velocity: centre_of_mass(bodies.map(|(s, m)| (s.velocity, m))),
+            angle: separation.to_angle(),
+            angular_velocity: separation.perp_dot(relative_vel) / separation.length_squared(),
+            origin_z: centre_of_mass_z(bodies.map(|(s, m)| (s.z, m))),
+        }
+    }
+
+    /// Converts an inertial position into this frame
+    pub fn pos_in_frame(&self, position: Vec2) -> Vec2 {
+        Vec2::from_angle(-self.angle).rotate(position - self.origin)
+    }
+
+    /// Converts an inertial position into this frame, including the out-of-plane component
+    pub fn pos3_in_frame(&self, position: Vec3) -> Vec3 {
+        self.pos_in_frame(position.xy())
+            .extend(position.z - self.origin_z)
+    }
+
+    /// Converts an inertial velocity of something at the inertial `position` into this frame
+    pub fn vel_in_frame(&self, position: Vec2, velocity: Vec2) -> Vec2 {
+        let offset = position - self.origin;
+        Vec2::from_angle(-self.angle)
+            .rotate(velocity - self.velocity - self.angular_velocity * offset.perp())
+    }
+
+    /// Rotates an inertial direction, like an acceleration or force, into this frame
+    pub fn dir_in_frame(&self, direction: Vec2) -> Vec2 {
+        Vec2::from_angle(-self.angle).rotate(direction)
+    }
+
+    /// Converts a position in this frame back into the inertial frame
+    pub fn pos_in_inertial(&self, position: Vec2) -> Vec2 {
+        self.origin + Vec2::from_angle(self.angle).rotate(position)
+    }
+
+    /// Converts a velocity of something at `position` in this frame back into the inertial frame
+    pub fn vel_in_inertial(&self, position: Vec2, velocity: Vec2) -> Vec2 {
+        let rotation = Vec2::from_angle(self.angle);
+        self.velocity
+            + rotation.rotate(velocity)
+            + self.angular_velocity * rotation.rotate(position).perp()
+    }
+}
+
+/// The [`ReferenceFrame`] at the current step of the simulation
+#[derive(Resource, Default)]
+pub struct CurrentFrame(pub FrameSnapshot);
+
+/// Places every body at its current position as seen from the [`ReferenceFrame`]
+pub(crate) fn apply_reference_frame(
+    sim: Res<SimData>,
+    mut frame: ResMut<ReferenceFrame>,
+    mut current_frame: ResMut<CurrentFrame>,
+    bodies: Query<(Entity, &Trajectory, &Mass)>,
+    mut transforms: Query<(&mut Transform, &Trajectory)>,
+) {
+    let bodies = bodies
+        .iter()
+        .map(|(entity, traj, mass)| (entity, traj, mass.0))
+        .collect::<Vec<_>>();
+
+    current_frame.0 = match frame.snapshots(&bodies, 0..1) {
+        Some(snapshots) => snapshots[0],
+        None => {
+            warn!("Reference body no longer exists, falling back to the inertial frame");
+            *frame = ReferenceFrame::Inertial;
+            FrameSnapshot::default()
+        }
+    };
+    // bodies are shown between the front and the next step when playing slower than a step per frame
+    let next_frame = (sim.step_fraction > 0.0)
+        .then(|| frame.snapshots(&bodies, 0..2))
+        .flatten()
+        .map(|snapshots| snapshots[1]);
+
+    for (mut transform, trajectory) in transforms.iter_mut() {
+        let Some(current) = trajectory.front() else {
+            continue;
+        };
+        let mut position = current_frame.0.pos_in_frame(current.position);
+
+        if let (Some(next_frame), Some(next)) = (next_frame, trajectory.0.get(1)) {
+            position = position.lerp(next_frame.pos_in_frame(next.position), sim.step_fraction);
+        }
+
+        transform.translation = position.extend(0.0);
+    }
+}
+
+pub(super) fn reset_reference_frame(mut frame: ResMut<ReferenceFrame>) {
+    *frame = ReferenceFrame::Inertial;
+}
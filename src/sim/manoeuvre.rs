@@ -0,0 +1,64 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::{orbit, SimClock, SimSnapshot};
+
+/// An impulsive burn changing a body's velocity at a given simulation time
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ManoeuvreNode {
+    pub time: f64,
+    /// Δv along the velocity relative to the body's dominant attractor
+    pub prograde: f32,
+    /// Δv away from the dominant attractor, perpendicular to the velocity
+    pub radial: f32,
+}
+
+impl ManoeuvreNode {
+    /// The step of the simulation the burn is applied at
+    pub fn step(&self) -> u64 {
+        SimClock::step_at(self.time)
+    }
+
+    pub fn delta_v(&self, (prograde, radial): (Vec3, Vec3)) -> Vec3 {
+        prograde * self.prograde + radial * self.radial
+    }
+}
+
+/// Burns planned for a body, sorted by time
+#[derive(Component, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Manoeuvres(pub Vec<ManoeuvreNode>);
+
+impl Manoeuvres {
+    pub fn insert(&mut self, node: ManoeuvreNode) -> usize {
+        let index = self.0.partition_point(|other| other.time <= node.time);
+        self.0.insert(index, node);
+        index
+    }
+
+    /// The burns applied at the given step
+    pub fn at_step(&self, step: u64) -> impl Iterator<Item = &ManoeuvreNode> {
+        self.0.iter().filter(move |node| node.step() == step)
+    }
+}
+
+/// Prograde and radial direction of the `body`th of the given bodies relative to its dominant
+/// attractor, or to the origin if it has none
+pub fn burn_axes(bodies: &[(SimSnapshot, f32)], body: usize) -> (Vec3, Vec3) {
+    let parents = orbit::dominant_attractors(
+        &bodies
+            .iter()
+            .map(|(state, mass)| (state.position3(), *mass))
+            .collect::<Vec<_>>(),
+    );
+    let state = bodies[body].0;
+    let parent = parents[body].map_or_else(SimSnapshot::default, |parent| bodies[parent].0);
+
+    let rel_pos = state.position3() - parent.position3();
+    let prograde = (state.velocity3() - parent.velocity3()).normalize_or_zero();
+    let radial = (rel_pos - prograde * rel_pos.dot(prograde))
+        .try_normalize()
+        // moving straight towards or away from the parent
+        .unwrap_or_else(|| prograde.cross(Vec3::Z).normalize_or_zero());
+
+    (prograde, radial)
+}
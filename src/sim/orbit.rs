@@ -0,0 +1,163 @@
+use core::f32::consts::TAU;
+
+use bevy::prelude::*;
+
+/// Below this eccentricity the periapsis is taken from the current position
+const CIRCULAR_EPSILON: f32 = 1e-6;
+
+/// Osculating Keplerian elements of a two-body orbit in the plane
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct OrbitalElements {
+    /// Negative for hyperbolic orbits
+    pub semi_major_axis: f32,
+    pub eccentricity: f32,
+    /// Angle of the periapsis, measured from the x axis
+    pub argument_of_periapsis: f32,
+    /// Angle between the periapsis and the current position, in the direction of motion
+    pub true_anomaly: f32,
+    /// Whether the orbit runs clockwise
+    pub clockwise: bool,
+}
+
+impl OrbitalElements {
+    /// Computes the elements from the position and velocity relative to the parent body.
+    /// `mu` is the gravitational constant times the mass of both bodies.
+    /// Returns `None` for degenerate (radial or parabolic) orbits.
+    pub fn from_state(position: Vec2, velocity: Vec2, mu: f32) -> Option<Self> {
+        let distance = position.length();
+        let angular_momentum = position.perp_dot(velocity);
+
+        if distance == 0.0 || angular_momentum == 0.0 || mu <= 0.0 {
+            return None;
+        }
+
+        let energy = specific_energy(position.extend(0.0), velocity.extend(0.0), mu);
+        let eccentricity_vec = ((velocity.length_squared() - mu / distance) * position
+            - position.dot(velocity) * velocity)
+            / mu;
+        let eccentricity = eccentricity_vec.length();
+
+        if energy == 0.0 || (eccentricity - 1.0).abs() < CIRCULAR_EPSILON {
+            return None;
+        }
+
+        let clockwise = angular_momentum < 0.0;
+        let periapsis_dir = if eccentricity < CIRCULAR_EPSILON {
+            position / distance
+        } else {
+            eccentricity_vec / eccentricity
+        };
+        let true_anomaly =
+            periapsis_dir.angle_between(position) * if clockwise { -1.0 } else { 1.0 };
+
+        Some(Self {
+            semi_major_axis: -mu / (2.0 * energy),
+            eccentricity,
+            argument_of_periapsis: periapsis_dir.to_angle(),
+            true_anomaly,
+            clockwise,
+        })
+    }
+
+    /// Position and velocity relative to the parent body, the inverse of [`Self::from_state`]
+    pub fn to_state(self, mu: f32) -> (Vec2, Vec2) {
+        let (sin, cos) = self.true_anomaly.sin_cos();
+        let (periapsis_dir, normal) = self.axes();
+
+        let velocity = (mu / self.semi_latus_rectum()).sqrt()
+            * (-sin * periapsis_dir + (self.eccentricity + cos) * normal);
+
+        (self.position_at(self.true_anomaly), velocity)
+    }
+
+    /// Position relative to the parent body at the given true anomaly
+    pub fn position_at(&self, true_anomaly: f32) -> Vec2 {
+        let (sin, cos) = true_anomaly.sin_cos();
+        let (periapsis_dir, normal) = self.axes();
+
+        let distance = self.semi_latus_rectum() / (1.0 + self.eccentricity * cos);
+        distance * (cos * periapsis_dir + sin * normal)
+    }
+
+    fn axes(&self) -> (Vec2, Vec2) {
+        let periapsis_dir = Vec2::from_angle(self.argument_of_periapsis);
+        let normal = periapsis_dir.perp() * if self.clockwise { -1.0 } else { 1.0 };
+        (periapsis_dir, normal)
+    }
+
+    /// The true anomaly the orbit approaches at infinity, `None` for bound orbits
+    pub fn true_anomaly_limit(&self) -> Option<f32> {
+        (!self.is_bound()).then(|| (-1.0 / self.eccentricity).acos())
+    }
+
+    pub fn is_bound(&self) -> bool {
+        self.eccentricity < 1.0
+    }
+
+    pub fn semi_latus_rectum(&self) -> f32 {
+        self.semi_major_axis * (1.0 - self.eccentricity.powi(2))
+    }
+
+    pub fn periapsis(&self) -> f32 {
+        self.semi_major_axis * (1.0 - self.eccentricity)
+    }
+
+    /// `None` for unbound orbits
+    pub fn apoapsis(&self) -> Option<f32> {
+        self.is_bound()
+            .then_some(self.semi_major_axis * (1.0 + self.eccentricity))
+    }
+
+    /// `None` for unbound orbits
+    pub fn period(&self, mu: f32) -> Option<f32> {
+        self.is_bound()
+            .then(|| TAU * (self.semi_major_axis.powi(3) / mu).sqrt())
+    }
+
+    pub fn specific_energy(&self, mu: f32) -> f32 {
+        -mu / (2.0 * self.semi_major_axis)
+    }
+}
+
+/// Advance of the periapsis per revolution caused by the first-order post-Newtonian correction
+/// (see [`super::post_newtonian`]), in radians
+pub fn relativistic_precession(mu: f32, elements: &OrbitalElements, speed_of_light: f32) -> f32 {
+    3.0 * TAU * mu / (speed_of_light.powi(2) * elements.semi_latus_rectum())
+}
+
+/// Orbital energy per unit mass of something at `position` and `velocity` relative to its parent
+pub fn specific_energy(position: Vec3, velocity: Vec3, mu: f32) -> f32 {
+    velocity.length_squared() / 2.0 - mu / position.length()
+}
+
+/// Finds the dominant attractor of every body out of `(position, mass)` pairs using spheres of
+/// influence. Going from heaviest to lightest, each body's parent is the heavier body with the
+/// smallest sphere of influence that contains it. The heaviest body has no parent and an infinite
+/// sphere of influence.
+///
+/// Returns the index of every body's parent.
+pub fn dominant_attractors(bodies: &[(Vec3, f32)]) -> Vec<Option<usize>> {
+    let mut by_mass = (0..bodies.len()).collect::<Vec<_>>();
+    by_mass.sort_by(|a, b| bodies[*b].1.total_cmp(&bodies[*a].1));
+
+    let mut parents = vec![None; bodies.len()];
+    let mut soi_radii = vec![f32::INFINITY; bodies.len()];
+
+    for (rank, &body) in by_mass.iter().enumerate() {
+        let (position, mass) = bodies[body];
+
+        let parent = by_mass[..rank]
+            .iter()
+            .copied()
+            .filter(|&other| bodies[other].0.distance(position) < soi_radii[other])
+            .min_by(|a, b| soi_radii[*a].total_cmp(&soi_radii[*b]));
+
+        if let Some(parent) = parent {
+            let (parent_pos, parent_mass) = bodies[parent];
+            parents[body] = Some(parent);
+            soi_radii[body] = parent_pos.distance(position) * (mass / parent_mass).powf(0.4);
+        }
+    }
+
+    parents
+}
@@ -0,0 +1,1204 @@
+use std::{collections::VecDeque, ops::Range};
+
+use bevy::{
+    prelude::*,
+    render::{
+        mesh::{
+            MeshVertexAttribute, MeshVertexBufferLayoutRef, PrimitiveTopology,
+            VertexAttributeValues,
+        },
+        render_asset::RenderAssetUsages,
+        render_resource::{
+            AsBindGroup, RenderPipelineDescriptor, ShaderRef, SpecializedMeshPipelineError,
+            VertexFormat,
+        },
+        view::NoFrustumCulling,
+    },
+    sprite::{Material2d, Material2dKey, MaterialMesh2dBundle, Mesh2dHandle},
+    utils::HashSet,
+};
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+    frame::{FrameSnapshot, ReferenceFrame},
+    Mass, SimClock, SimData, SimSnapshot, Trajectory, TrajectoryVisibility, TIME_STEP,
+};
+use crate::controls::SimCamera;
+
+/// Points closer than this to the previous vertex on screen, in pixels, are left out of the line
+pub(crate) const DECIMATION_TOLERANCE: f32 = 1.5;
+
+/// Lines are decimated again once the camera zoomed in or out by more than this factor. Their
+/// width and dashes follow the zoom in the shader.
+const REBUILD_ZOOM: f32 = 2.0;
+
+/// Length of the dashes and the gaps between them, in pixels
+pub(crate) const DASH_LENGTH: f32 = 8.0;
+
+const LINE_SHADER: &str = "shaders/trajectory_line.wgsl";
+
+/// Direction of the segment a vertex belongs to in world units, and the offset of the vertex from
+/// the middle of the line to its edge in pixels, negative on the other side
+pub(crate) const ATTRIBUTE_LINE_SEGMENT: MeshVertexAttribute =
+    MeshVertexAttribute::new("LineSegment", 383_125_901, VertexFormat::Float32x4);
+
+/// Length of the line up to a vertex in world units, and the length of the dashes in pixels, or 0
+/// for a solid line
+pub(crate) const ATTRIBUTE_LINE_DASH: MeshVertexAttribute =
+    MeshVertexAttribute::new("LineDash", 383_125_902, VertexFormat::Float32x2);
+
+/// How a line fades out towards its end
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FadeCurve {
+    Linear,
+    Quadratic,
+    /// Fully opaque until the end of the line
+    None,
+}
+
+impl FadeCurve {
+    pub fn label(&self) -> &'static str {
+        match self {
+            FadeCurve::Linear => "Linear",
+            FadeCurve::Quadratic => "Quadratic",
+            FadeCurve::None => "None",
+        }
+    }
+
+    /// Opacity of the line at `age`, from 0 at the body to 1 at the end of the line
+    fn alpha(&self, age: f32) -> f32 {
+        match self {
+            FadeCurve::Linear => 1.0 - age,
+            FadeCurve::Quadratic => (1.0 - age).powi(2),
+            FadeCurve::None => 1.0,
+        }
+    }
+}
+
+/// How the predicted trajectory of a body is drawn
+#[derive(Component, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TrajectoryStyle {
+    /// In pixels
+    pub width: f32,
+    /// Colour of the line instead of the body's colour
+    pub colour: Option<Color>,
+    pub dashed: bool,
+    pub fade: FadeCurve,
+    /// Opacity at the body
+    pub opacity: f32,
+    /// Colours the line from blue to red by the speed of the body in the reference frame
+    pub colour_by_speed: bool,
+}
+
+impl Default for TrajectoryStyle {
+    fn default() -> Self {
+        Self {
+            width: 2.0,
+            colour: None,
+            dashed: false,
+            fade: FadeCurve::Linear,
+            opacity: 0.7,
+            colour_by_speed: false,
+        }
+    }
+}
+
+/// Settings of the history trails showing where bodies have been
+#[derive(Resource, Clone, PartialEq)]
+pub struct TrailSettings {
+    pub enabled: bool,
+    /// How long positions are kept, in simulation time
+    pub duration: f32,
+    pub fade: FadeCurve,
+    /// Draw the trail in the colour of the body instead of [`TrailSettings::colour`]
+    pub body_colour: bool,
+    pub colour: Color,
+    pub opacity: f32,
+}
+
+impl Default for TrailSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            duration: 10.0,
+            fade: FadeCurve::Linear,
+            body_colour: true,
+            colour: Color::WHITE,
+            opacity: 0.5,
+        }
+    }
+}
+
+impl TrailSettings {
+    /// Longest duration that can be chosen, every body keeps a state per step of it
+    pub const MAX_DURATION: f32 = 1000.0;
+
+    /// Number of past steps that are kept
+    pub(super) fn len(&self) -> usize {
+        (self.duration / TIME_STEP) as usize
+    }
+}
+
+/// Past states of a body, most recent first
+#[derive(Component, Default)]
+pub(super) struct Trail(Trajectory);
+
+impl Trail {
+    /// Adds the states a body passed through, oldest first, dropping states older than `len` steps
+    pub(super) fn record<'a>(&mut self, passed: impl Iterator<Item = &'a SimSnapshot>, len: usize) {
+        let history = &mut (self.0).0;
+        for snapshot in passed {
+            history.push_front(*snapshot);
+        }
+        history.truncate(len);
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum LineKind {
+    /// The predicted trajectory
+    Prediction,
+    /// Where the body has been
+    Trail,
+}
+
+/// Line strip mesh showing the predicted trajectory or the trail of `body`
+#[derive(Component)]
+pub(super) struct TrajectoryLine {
+    body: Entity,
+    kind: LineKind,
+    built_colour: Option<Color>,
+    built: Option<BuiltLine>,
+}
+
+/// The steps of the simulation a line shows and where they are in the trajectory or trail
+#[derive(Clone, Copy)]
+pub(crate) struct LineSteps {
+    pub kind: LineKind,
+    /// Current step of the simulation
+    pub now: u64,
+    /// Number of states in the trajectory or trail
+    pub len: u64,
+}
+
+impl LineSteps {
+    fn range(&self) -> Range<u64> {
+        match self.kind {
+            LineKind::Prediction => self.now..self.now + self.len,
+            LineKind::Trail => self.now.saturating_sub(self.len)..self.now,
+        }
+    }
+
+    /// Index of `step` in the trajectory, or in the trail, which is most recent first
+    fn index_of(&self, step: u64) -> usize {
+        match self.kind {
+            LineKind::Prediction => (step - self.now) as usize,
+            LineKind::Trail => (self.now - 1 - step) as usize,
+        }
+    }
+
+    fn indices_from(&self, step: u64) -> Range<usize> {
+        match self.kind {
+            LineKind::Prediction => self.index_of(step)..self.len as usize,
+            LineKind::Trail => 0..self.index_of(step) + 1,
+        }
+    }
+
+    fn point_at(
+        &self,
+        history: &Trajectory,
+        step: u64,
+        frame: &FrameSnapshot,
+        shape: &LineShape,
+    ) -> LinePoint {
+        let snapshot = history.0[self.index_of(step)];
+        let position = frame.pos3_in_frame(snapshot.position3());
+        LinePoint {
+            position: if shape.in_plane {
+                position.with_z(0.0)
+            } else {
+                position
+            },
+            step,
+            speed: frame
+                .vel_in_frame(snapshot.position, snapshot.velocity)
+                .length(),
+        }
+    }
+}
+
+/// A point of a line in the reference frame
+#[derive(Clone, Copy)]
+struct LinePoint {
+    position: Vec3,
+    step: u64,
+    speed: f32,
+}
+
+/// Part of a line between two points that were kept by the decimation
+#[derive(Clone, Copy)]
+struct Segment {
+    start: LinePoint,
+    end: LinePoint,
+}
+
+/// Vertices of a line mesh, two triangles per [`Segment`]
+#[derive(Default)]
+struct LineVertices {
+    /// On the middle of the line, the shader moves them out to its edges
+    positions: Vec<[f32; 3]>,
+    segments: Vec<[f32; 4]>,
+    dashes: Vec<[f32; 2]>,
+}
+
+impl LineVertices {
+    const PER_SEGMENT: usize = 6;
+
+    fn take(mesh: &mut Mesh) -> Self {
+        fn take<T>(
+            mesh: &mut Mesh,
+            attribute: MeshVertexAttribute,
+            values: impl Fn(&mut VertexAttributeValues) -> Option<&mut Vec<T>>,
+        ) -> Vec<T> {
+            mesh.attribute_mut(attribute)
+                .and_then(values)
+                .map(std::mem::take)
+                .unwrap_or_default()
+        }
+
+        Self {
+            positions: take(mesh, Mesh::ATTRIBUTE_POSITION, |values| match values {
+                VertexAttributeValues::Float32x3(values) => Some(values),
+                _ => None,
+            }),
+            segments: take(mesh, ATTRIBUTE_LINE_SEGMENT, |values| match values {
+                VertexAttributeValues::Float32x4(values) => Some(values),
+                _ => None,
+            }),
+            dashes: take(mesh, ATTRIBUTE_LINE_DASH, |values| match values {
+                VertexAttributeValues::Float32x2(values) => Some(values),
+                _ => None,
+            }),
+        }
+    }
+
+    fn insert_into(self, mesh: &mut Mesh) {
+        mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, self.positions);
+        mesh.insert_attribute(ATTRIBUTE_LINE_SEGMENT, self.segments);
+        mesh.insert_attribute(ATTRIBUTE_LINE_DASH, self.dashes);
+    }
+
+    fn clear(&mut self) {
+        self.positions.clear();
+        self.segments.clear();
+        self.dashes.clear();
+    }
+
+    fn drain_front(&mut self, segments: usize) {
+        let end = (segments * Self::PER_SEGMENT).min(self.positions.len());
+        self.positions.drain(..end);
+        self.segments.drain(..end);
+        self.dashes.drain(..end);
+    }
+
+    fn pop_segment(&mut self) {
+        let len = self.positions.len().saturating_sub(Self::PER_SEGMENT);
+        self.positions.truncate(len);
+        self.segments.truncate(len);
+        self.dashes.truncate(len);
+    }
+
+    fn push_segment(&mut self, (a, b): (Vec3, Vec3), distance: (f32, f32), shape: &LineShape) {
+        let offset = shape.width / 2.0;
+        let dash = shape.dash.unwrap_or(0.0);
+
+        for (position, offset, distance) in [
+            (a, offset, distance.0),
+            (a, -offset, distance.0),
+            (b, offset, distance.1),
+            (b, offset, distance.1),
+            (a, -offset, distance.0),
+            (b, -offset, distance.1),
+        ] {
+            self.positions.push(position.to_array());
+            self.segments.push((b - a).extend(offset).to_array());
+            self.dashes.push([distance, dash]);
+        }
+    }
+}
+
+/// What a line mesh was built from, so that it can be extended and cut as the simulation advances
+/// instead of being rebuilt
+#[derive(Default)]
+pub(crate) struct BuiltLine {
+    /// Oldest first, each has [`LineVertices::PER_SEGMENT`] vertices in the same order
+    segments: VecDeque<Segment>,
+    last: Option<LinePoint>,
+    /// Tells if the trajectory has been recomputed since the newest point was added
+    last_state: Option<(SimSnapshot, FrameSnapshot)>,
+    travelled: f32,
+    /// Whether the newest point is too close to the vertex before and only kept for now
+    tentative: bool,
+    coloured_at: Option<u64>,
+}
+
+impl BuiltLine {
+    fn build(
+        vertices: &mut LineVertices,
+        history: &Trajectory,
+        steps: LineSteps,
+        frames: &[FrameSnapshot],
+        shape: &LineShape,
+    ) -> Self {
+        let covered = steps
+            .range()
+            .filter(|step| steps.index_of(*step) < frames.len())
+            .collect::<Vec<_>>();
+
+        let mut built = Self::default();
+        vertices.clear();
+        built.extend(
+            vertices,
+            covered
+                .iter()
+                .map(|step| steps.point_at(history, *step, &frames[steps.index_of(*step)], shape)),
+            shape,
+        );
+        built.last_state = covered.last().map(|step| {
+            let index = steps.index_of(*step);
+            (history.0[index], frames[index])
+        });
+        built
+    }
+
+    fn tail_frames(
+        &self,
+        history: &Trajectory,
+        steps: LineSteps,
+        frame: &ReferenceFrame,
+        all: &[(Entity, &Trajectory, f32)],
+    ) -> Option<Vec<FrameSnapshot>> {
+        let (last, last_state) = (self.last?, self.last_state?);
+        if !steps.range().contains(&last.step) {
+            return None;
+        }
+
+        let indices = steps.indices_from(last.step);
+        let mut frames = frame_snapshots_in(frame, all, indices.clone())?;
+        if frames.is_empty() || (frames.len() < indices.len() && steps.kind == LineKind::Trail) {
+            return None;
+        }
+        if steps.kind == LineKind::Trail {
+            frames.reverse();
+        }
+        // a recomputed trajectory no longer passes through the newest point
+        let state = (history.0[steps.index_of(last.step)], frames[0]);
+        (state == last_state).then_some(frames)
+    }
+
+    fn is_current(&self, frames: &[FrameSnapshot], steps: LineSteps) -> bool {
+        frames.len() == 1
+            && self
+                .segments
+                .front()
+                .is_none_or(|segment| segment.end.step > steps.range().start)
+            && self.coloured_at == Some(steps.now)
+    }
+
+    /// Returns whether the line changed
+    fn advance(
+        &mut self,
+        vertices: &mut LineVertices,
+        history: &Trajectory,
+        steps: LineSteps,
+        frames: &[FrameSnapshot],
+        shape: &LineShape,
+    ) -> bool {
+        let cut = self.cut(vertices, steps.range().start);
+
+        let (Some(last), Some((_, new_frames))) = (self.last, frames.split_first()) else {
+            return cut > 0;
+        };
+        let Some(newest_frame) = new_frames.last() else {
+            return cut > 0;
+        };
+
+        self.extend(
+            vertices,
+            new_frames
+                .iter()
+                .enumerate()
+                .map(|(i, frame)| steps.point_at(history, last.step + 1 + i as u64, frame, shape)),
+            shape,
+        );
+        let newest = last.step + new_frames.len() as u64;
+        self.last_state = Some((history.0[steps.index_of(newest)], *newest_frame));
+        true
+    }
+
+    /// The newest point is always kept, others too close to the last vertex are left out
+    fn extend(
+        &mut self,
+        vertices: &mut LineVertices,
+        points: impl ExactSizeIterator<Item = LinePoint>,
+        shape: &LineShape,
+    ) {
+        let count = points.len();
+        if count == 0 {
+            return;
+        }
+
+        if self.tentative {
+            if let Some(segment) = self.segments.pop_back() {
+                vertices.pop_segment();
+                self.travelled -= segment.start.position.distance(segment.end.position);
+                self.last = Some(segment.start);
+            }
+            self.tentative = false;
+        }
+
+        for (i, point) in points.enumerate() {
+            let Some(last) = self.last else {
+                self.last = Some(point);
+                continue;
+            };
+            let too_close =
+                last.position.distance_squared(point.position) < shape.tolerance * shape.tolerance;
+            let is_end = i + 1 == count;
+            if too_close && !is_end {
+                continue;
+            }
+            self.tentative = too_close;
+
+            let start = self.travelled;
+            self.travelled += last.position.distance(point.position);
+            vertices.push_segment(
+                (last.position, point.position),
+                (start, self.travelled),
+                shape,
+            );
+
+            self.segments.push_back(Segment {
+                start: last,
+                end: point,
+            });
+            self.last = Some(point);
+        }
+    }
+
+    /// Returns how many segments were dropped
+    fn cut(&mut self, vertices: &mut LineVertices, first_step: u64) -> usize {
+        let mut cut = 0;
+        while self
+            .segments
+            .front()
+            .is_some_and(|segment| segment.end.step <= first_step)
+        {
+            self.segments.pop_front();
+            cut += 1;
+        }
+        vertices.drain_front(cut);
+        cut
+    }
+
+    fn colours(&self, colour_at: impl Fn(&LinePoint) -> LinearRgba) -> Vec<[f32; 4]> {
+        self.segments
+            .iter()
+            .flat_map(|segment| {
+                let [a, b] = [segment.start, segment.end].map(|point| colour_at(&point));
+                [a, a, b, b, a, b].map(|colour| colour.to_f32_array())
+            })
+            .collect()
+    }
+
+    fn speed_range(&self) -> (f32, f32) {
+        self.segments
+            .iter()
+            .flat_map(|segment| [segment.start.speed, segment.end.speed])
+            .fold((f32::INFINITY, 0.0f32), |(min, max), speed| {
+                (min.min(speed), max.max(speed))
+            })
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct LineCache {
+    scale: f32,
+    trajectory_len: usize,
+    /// Trail settings the trails were last built with. The settings window touches them every
+    /// frame, so change detection can't be relied on.
+    trails: Option<TrailSettings>,
+}
+
+/// What changed since the lines were last updated
+pub(crate) struct LineChanges {
+    /// The camera zoomed far enough for the decimation to show
+    pub rescaled: bool,
+    /// The trajectories cover a different number of steps
+    pub resized: bool,
+    pub trails_changed: bool,
+}
+
+impl LineCache {
+    /// Remembers the zoom, trajectory length and trail settings the lines are updated for
+    pub(crate) fn update(
+        &mut self,
+        scale: f32,
+        trajectory_len: usize,
+        trails: Option<&TrailSettings>,
+    ) -> LineChanges {
+        let zoom = scale / self.scale;
+        let rescaled = !(1.0 / REBUILD_ZOOM..REBUILD_ZOOM).contains(&zoom);
+        if rescaled {
+            self.scale = scale;
+        }
+
+        let resized = self.trajectory_len != trajectory_len;
+        self.trajectory_len = trajectory_len;
+
+        let trails_changed = self.trails.as_ref() != trails;
+        if trails_changed {
+            self.trails = trails.cloned();
+        }
+
+        LineChanges {
+            rescaled,
+            resized,
+            trails_changed,
+        }
+    }
+}
+
+pub(crate) fn line_mesh() -> Mesh {
+    Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::default(),
+    )
+    .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, Vec::<[f32; 3]>::new())
+    .with_inserted_attribute(Mesh::ATTRIBUTE_COLOR, Vec::<[f32; 4]>::new())
+    .with_inserted_attribute(ATTRIBUTE_LINE_SEGMENT, Vec::<[f32; 4]>::new())
+    .with_inserted_attribute(ATTRIBUTE_LINE_DASH, Vec::<[f32; 2]>::new())
+}
+
+/// Shape of a line
+pub(crate) struct LineShape {
+    /// Points closer than this to the previous vertex are left out, in world units
+    pub tolerance: f32,
+    /// In pixels
+    pub width: f32,
+    /// In pixels
+    pub dash: Option<f32>,
+    /// Leaves out the out-of-plane components of the positions, for the 2D view
+    pub in_plane: bool,
+}
+
+/// Draws the lines with the same width and dashes on screen at every zoom level. The vertices lie
+/// on the middle of the line and the shader moves them out to its edges.
+#[derive(Asset, TypePath, AsBindGroup, Clone)]
+pub(super) struct LineMaterial {
+    /// World units per pixel
+    #[uniform(0)]
+    scale: f32,
+}
+
+impl Material2d for LineMaterial {
+    fn vertex_shader() -> ShaderRef {
+        LINE_SHADER.into()
+    }
+
+    fn fragment_shader() -> ShaderRef {
+        LINE_SHADER.into()
+    }
+
+    fn specialize(
+        descriptor: &mut RenderPipelineDescriptor,
+        layout: &MeshVertexBufferLayoutRef,
+        _key: Material2dKey<Self>,
+    ) -> Result<(), SpecializedMeshPipelineError> {
+        let vertex_layout = layout.0.get_layout(&[
+            Mesh::ATTRIBUTE_POSITION.at_shader_location(0),
+            Mesh::ATTRIBUTE_COLOR.at_shader_location(1),
+            ATTRIBUTE_LINE_SEGMENT.at_shader_location(2),
+            ATTRIBUTE_LINE_DASH.at_shader_location(3),
+        ])?;
+        descriptor.vertex.buffers = vec![vertex_layout];
+        Ok(())
+    }
+}
+
+fn speed_colour(t: f32) -> Color {
+    Color::hsl(240.0 * (1.0 - t), 1.0, 0.5)
+}
+
+/// Colours of a predicted trajectory, fading out from the body towards the prediction horizon
+pub(crate) fn prediction_colours(
+    built: &BuiltLine,
+    style: &TrajectoryStyle,
+    body_colour: Color,
+    now: u64,
+    trajectory_len: usize,
+) -> Vec<[f32; 4]> {
+    let colour = style.colour.unwrap_or(body_colour);
+    let (min_speed, max_speed) = built.speed_range();
+
+    built.colours(|point| {
+        let colour = if style.colour_by_speed {
+            speed_colour((point.speed - min_speed) / (max_speed - min_speed).max(f32::EPSILON))
+        } else {
+            colour
+        };
+        let age = (point.step.saturating_sub(now)) as f32 / trajectory_len as f32;
+        colour
+            .with_alpha(style.opacity * style.fade.alpha(age))
+            .to_linear()
+    })
+}
+
+fn trail_colours(
+    built: &BuiltLine,
+    trails: &TrailSettings,
+    body_colour: Color,
+    now: u64,
+) -> Vec<[f32; 4]> {
+    let colour = if trails.body_colour {
+        body_colour
+    } else {
+        trails.colour
+    };
+    let trail_len = trails.len().max(1) as f32;
+
+    built.colours(|point| {
+        let age = (now.saturating_sub(point.step + 1)) as f32 / trail_len;
+        colour
+            .with_alpha(trails.opacity * trails.fade.alpha(age))
+            .to_linear()
+    })
+}
+
+/// Stops early if the bodies the frame depends on have shorter trajectories than the others
+fn frame_snapshots_in(
+    frame: &ReferenceFrame,
+    trajectories: &[(Entity, &Trajectory, f32)],
+    steps: Range<usize>,
+) -> Option<Vec<FrameSnapshot>> {
+    let len = frame.available_steps(trajectories)?;
+    frame.snapshots(trajectories, steps.start.min(len)..steps.end.min(len))
+}
+
+/// Computes the frame at every step of the given trajectories, or of as many steps as the
+/// trajectories of the bodies the frame depends on cover
+pub(crate) fn frame_snapshots(
+    frame: &ReferenceFrame,
+    trajectories: &[(Entity, &Trajectory, f32)],
+) -> Option<Vec<FrameSnapshot>> {
+    frame_snapshots_in(frame, trajectories, 0..usize::MAX)
+}
+
+/// A line to bring up to date with [`update_line`]
+pub(crate) struct LineUpdate<'a> {
+    /// The trajectory or trail the line shows
+    pub history: &'a Trajectory,
+    /// The trajectories or trails of every body, to compute the frame from
+    pub all: &'a [(Entity, &'a Trajectory, f32)],
+    pub steps: LineSteps,
+    pub shape: LineShape,
+    /// The line can't be extended, because how it's drawn changed
+    pub rebuild: bool,
+    /// The colours have to be updated even if the line didn't move
+    pub recolour: bool,
+}
+
+/// Brings the mesh of a line up to date: extends it as the simulation advances, rebuilds it when
+/// it can't be extended and updates its colours whenever it moved. `all_frames` keeps the frame at
+/// every step for the lines that are rebuilt. Returns whether the colours were updated.
+pub(crate) fn update_line(
+    built: &mut Option<BuiltLine>,
+    line: LineUpdate,
+    frame: &ReferenceFrame,
+    all_frames: &mut Option<Option<Vec<FrameSnapshot>>>,
+    meshes: &mut Assets<Mesh>,
+    mesh: &Handle<Mesh>,
+    colours: impl FnOnce(&BuiltLine) -> Vec<[f32; 4]>,
+) -> bool {
+    let LineUpdate {
+        history,
+        all,
+        steps,
+        shape,
+        rebuild,
+        recolour,
+    } = line;
+
+    let tail = built
+        .as_ref()
+        .filter(|_| !rebuild)
+        .and_then(|built| built.tail_frames(history, steps, frame, all));
+
+    let idle = tail
+        .as_ref()
+        .is_some_and(|frames| built.as_ref().unwrap().is_current(frames, steps));
+    if idle && !recolour {
+        return false;
+    }
+    // only touch the meshes that change, every change is sent to the GPU again
+    let Some(mesh) = meshes.get_mut(mesh) else {
+        return false;
+    };
+
+    let moved = match (tail, built.as_mut()) {
+        (Some(frames), Some(line)) => {
+            let mut vertices = LineVertices::take(mesh);
+            let advanced = line.advance(&mut vertices, history, steps, &frames, &shape);
+            vertices.insert_into(mesh);
+            advanced || line.coloured_at != Some(steps.now)
+        }
+        _ => {
+            let Some(frames) = all_frames.get_or_insert_with(|| frame_snapshots(frame, all)) else {
+                return false;
+            };
+
+            let mut vertices = LineVertices::take(mesh);
+            *built = Some(BuiltLine::build(
+                &mut vertices,
+                history,
+                steps,
+                frames,
+                &shape,
+            ));
+            vertices.insert_into(mesh);
+            true
+        }
+    };
+
+    // the fade follows the body, so the colours change whenever the line moves along
+    if !moved && !recolour {
+        return false;
+    }
+    let line = built.as_mut().unwrap();
+    mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, colours(line));
+    line.coloured_at = Some(steps.now);
+    true
+}
+
+/// Keeps a line strip mesh for the trajectory and the trail of every body up to date. As the
+/// simulation advances, the steps that were passed are cut from the lines and the new ones
+/// appended. Lines are only rebuilt when the reference frame changed, the camera zoomed far enough
+/// for the decimation to show, or their own body's trajectory was recomputed.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub(super) fn update_trajectory_lines(
+    sim: Res<SimData>,
+    clock: Res<SimClock>,
+    frame: Res<ReferenceFrame>,
+    trails: Res<TrailSettings>,
+    bodies: Query<(
+        Entity,
+        Ref<Trajectory>,
+        Option<Ref<Trail>>,
+        Option<Ref<TrajectoryStyle>>,
+        Ref<TrajectoryVisibility>,
+        &Handle<ColorMaterial>,
+        &Mass,
+    )>,
+    mut lines: Query<(Entity, &mut TrajectoryLine, &Mesh2dHandle, &mut Visibility)>,
+    camera: Query<&OrthographicProjection, With<SimCamera>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    materials: Res<Assets<ColorMaterial>>,
+    mut line_materials: ResMut<Assets<LineMaterial>>,
+    mut cache: Local<LineCache>,
+    mut material: Local<Option<Handle<LineMaterial>>>,
+    mut cmds: Commands,
+) {
+    let scale = camera.single().scale;
+    // shared by all lines, the colour comes from the vertices
+    let material = material
+        .get_or_insert_with(|| line_materials.add(LineMaterial { scale }))
+        .clone();
+    // only touched when the zoom changes, as every change is sent to the GPU again
+    if line_materials
+        .get(&material)
+        .is_some_and(|material| material.scale != scale)
+    {
+        line_materials.get_mut(&material).unwrap().scale = scale;
+    }
+
+    // the inspector touches the simulation data every frame, compare the values instead
+    let LineChanges {
+        rescaled,
+        resized,
+        trails_changed,
+    } = cache.update(scale, sim.trajectory_len(), Some(&trails));
+
+    let predictions = bodies
+        .iter()
+        .map(|(entity, traj, .., mass)| (entity, traj.into_inner(), mass.0))
+        .collect::<Vec<_>>();
+    let trail_histories = bodies
+        .iter()
+        .filter_map(|(entity, _, trail, .., mass)| Some((entity, &trail?.into_inner().0, mass.0)))
+        .collect::<Vec<_>>();
+    let mut prediction_frames = None;
+    let mut trail_frames = None;
+
+    for (line_entity, mut line, mesh, mut visibility) in lines.iter_mut() {
+        let Ok((entity, traj, trail, style, vis, mat_handle, _)) = bodies.get(line.body) else {
+            cmds.entity(line_entity).despawn();
+            continue;
+        };
+
+        // the body the frame is centred on would only be drawn as a point
+        let visible = vis.0
+            && *frame != ReferenceFrame::Body(entity)
+            && (line.kind == LineKind::Prediction || trails.enabled);
+        *visibility = if visible {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+
+        let (history, all) = match line.kind {
+            LineKind::Prediction => (&*traj, &predictions),
+            LineKind::Trail => match trail {
+                Some(trail) => (&trail.into_inner().0, &trail_histories),
+                None => continue,
+            },
+        };
+        if !visible {
+            // hidden lines aren't kept up to date
+            line.built = None;
+            continue;
+        }
+
+        let body_colour = materials
+            .get(mat_handle)
+            .map_or(Color::WHITE, |material| material.color);
+        let style_changed = style.as_ref().is_some_and(|style| style.is_changed());
+        let style = style.map_or_else(TrajectoryStyle::default, |style| style.clone());
+
+        let steps = LineSteps {
+            kind: line.kind,
+            now: clock.steps,
+            len: history.0.len() as u64,
+        };
+        let shape = LineShape {
+            tolerance: DECIMATION_TOLERANCE * scale,
+            width: style.width,
+            dash: (style.dashed && line.kind == LineKind::Prediction).then_some(DASH_LENGTH),
+            in_plane: true,
+        };
+
+        let rebuild = rescaled
+            || frame.is_changed()
+            || style_changed
+            || vis.is_changed()
+            || (line.kind == LineKind::Trail && trails_changed)
+            || line.built_colour != Some(body_colour);
+
+        let recoloured = update_line(
+            &mut line.built,
+            LineUpdate {
+                history,
+                all,
+                steps,
+                shape,
+                rebuild,
+                recolour: resized || trails_changed,
+            },
+            &frame,
+            match steps.kind {
+                LineKind::Prediction => &mut prediction_frames,
+                LineKind::Trail => &mut trail_frames,
+            },
+            &mut meshes,
+            &mesh.0,
+            |built| match steps.kind {
+                LineKind::Prediction => prediction_colours(
+                    built,
+                    &style,
+                    body_colour,
+                    clock.steps,
+                    sim.trajectory_len(),
+                ),
+                LineKind::Trail => trail_colours(built, &trails, body_colour, clock.steps),
+            },
+        );
+        if recoloured {
+            line.built_colour = Some(body_colour);
+        }
+    }
+
+    let with_line = lines
+        .iter()
+        .map(|(_, line, ..)| (line.body, line.kind))
+        .collect::<HashSet<_>>();
+
+    for (entity, ..) in bodies.iter() {
+        for (kind, z) in [(LineKind::Prediction, -1.0), (LineKind::Trail, -2.0)] {
+            if with_line.contains(&(entity, kind)) {
+                continue;
+            }
+
+            cmds.spawn((
+                MaterialMesh2dBundle {
+                    mesh: Mesh2dHandle(meshes.add(line_mesh())),
+                    material: material.clone(),
+                    // behind the bodies
+                    transform: Transform::from_xyz(0.0, 0.0, z),
+                    ..default()
+                },
+                // the mesh grows and moves with the trajectory and the shader widens it
+                NoFrustumCulling,
+                TrajectoryLine {
+                    body: entity,
+                    kind,
+                    built_colour: None,
+                    built: None,
+                },
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A body moving along the x axis, one unit per step, and speeding up by one unit per step
+    fn straight_history(len: usize) -> Trajectory {
+        Trajectory(
+            (0..len)
+                .map(|i| SimSnapshot {
+                    position: Vec2::new(i as f32, 0.0),
+                    velocity: Vec2::new(i as f32 + 1.0, 0.0),
+                    ..default()
+                })
+                .collect(),
+        )
+    }
+
+    fn shape(tolerance: f32) -> LineShape {
+        LineShape {
+            tolerance,
+            width: 2.0,
+            dash: None,
+            in_plane: true,
+        }
+    }
+
+    fn prediction_steps(now: u64, len: usize) -> LineSteps {
+        LineSteps {
+            kind: LineKind::Prediction,
+            now,
+            len: len as u64,
+        }
+    }
+
+    fn build(history: &Trajectory, steps: LineSteps, tolerance: f32) -> (BuiltLine, LineVertices) {
+        let mut vertices = LineVertices::default();
+        let frames = vec![FrameSnapshot::default(); history.0.len()];
+        let built = BuiltLine::build(&mut vertices, history, steps, &frames, &shape(tolerance));
+        (built, vertices)
+    }
+
+    #[test]
+    fn steps_index_predictions_and_trails() {
+        let prediction = prediction_steps(10, 5);
+        assert_eq!(prediction.range(), 10..15);
+        assert_eq!(prediction.index_of(12), 2);
+        assert_eq!(prediction.indices_from(12), 2..5);
+
+        let trail = LineSteps {
+            kind: LineKind::Trail,
+            now: 10,
+            len: 5,
+        };
+        assert_eq!(trail.range(), 5..10);
+        assert_eq!(trail.index_of(9), 0);
+        assert_eq!(trail.index_of(5), 4);
+        assert_eq!(trail.indices_from(7), 0..3);
+    }
+
+    #[test]
+    fn build_decimates_but_keeps_the_newest_point() {
+        let history = straight_history(6);
+        let (built, vertices) = build(&history, prediction_steps(0, 6), 2.0);
+
+        // 0 -> 2 -> 4 and the too close 5 at the end
+        assert_eq!(built.segments.len(), 3);
+        assert!(built.tentative);
+        assert_eq!(built.last.unwrap().step, 5);
+        assert_eq!(built.travelled, 5.0);
+        assert_eq!(
+            vertices.positions.len(),
+            built.segments.len() * LineVertices::PER_SEGMENT
+        );
+        assert!(built.last_state.unwrap().0 == history.0[5]);
+    }
+
+    #[test]
+    fn extend_replaces_the_tentative_segment() {
+        let history = straight_history(6);
+        let (mut built, mut vertices) = build(&history, prediction_steps(0, 6), 2.0);
+
+        let points = (6..8u32).map(|step| LinePoint {
+            position: Vec3::new(step as f32, 0.0, 0.0),
+            step: step.into(),
+            speed: 1.0,
+        });
+        built.extend(&mut vertices, points, &shape(2.0));
+
+        // 4 -> 5 is dropped again for 4 -> 6 and the too close 7 at the end
+        let ends = built
+            .segments
+            .iter()
+            .map(|segment| segment.end.step)
+            .collect::<Vec<_>>();
+        assert_eq!(ends, [2, 4, 6, 7]);
+        assert_eq!(built.travelled, 7.0);
+        assert_eq!(
+            vertices.positions.len(),
+            built.segments.len() * LineVertices::PER_SEGMENT
+        );
+        // the dashes continue along the line
+        assert_eq!(vertices.dashes.last().unwrap()[0], 7.0);
+    }
+
+    #[test]
+    fn cut_drops_passed_segments_and_their_vertices() {
+        let history = straight_history(5);
+        let (mut built, mut vertices) = build(&history, prediction_steps(0, 5), 0.5);
+        assert_eq!(built.segments.len(), 4);
+
+        assert_eq!(built.cut(&mut vertices, 2), 2);
+        assert_eq!(built.segments.front().unwrap().start.step, 2);
+        assert_eq!(vertices.positions.len(), 2 * LineVertices::PER_SEGMENT);
+        assert_eq!(vertices.positions[0], [2.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn advance_follows_the_simulation() {
+        let mut history = straight_history(4);
+        let (mut built, mut vertices) = build(&history, prediction_steps(0, 4), 0.5);
+
+        // the simulation moved on by a step and computed a new one
+        history.advance(1);
+        history.push_back(SimSnapshot {
+            position: Vec2::new(4.0, 0.0),
+            velocity: Vec2::new(5.0, 0.0),
+            ..default()
+        });
+        let steps = prediction_steps(1, 4);
+        let all = [(Entity::PLACEHOLDER, &history, 1.0)];
+
+        let frames = built
+            .tail_frames(&history, steps, &ReferenceFrame::Inertial, &all)
+            .unwrap();
+        assert_eq!(frames.len(), 2);
+        assert!(!built.is_current(&frames, steps));
+
+        assert!(built.advance(&mut vertices, &history, steps, &frames, &shape(0.5)));
+        let ends = built
+            .segments
+            .iter()
+            .map(|segment| segment.end.step)
+            .collect::<Vec<_>>();
+        assert_eq!(ends, [2, 3, 4]);
+        assert_eq!(
+            vertices.positions.len(),
+            built.segments.len() * LineVertices::PER_SEGMENT
+        );
+
+        built.coloured_at = Some(1);
+        let frames = built
+            .tail_frames(&history, steps, &ReferenceFrame::Inertial, &all)
+            .unwrap();
+        assert!(built.is_current(&frames, steps));
+    }
+
+    #[test]
+    fn recomputed_trajectories_are_rebuilt() {
+        let mut history = straight_history(4);
+        let (built, _) = build(&history, prediction_steps(0, 4), 0.5);
+
+        history.0[3].position.y = 1.0;
+        let all = [(Entity::PLACEHOLDER, &history, 1.0)];
+        let tail = built.tail_frames(
+            &history,
+            prediction_steps(0, 4),
+            &ReferenceFrame::Inertial,
+            &all,
+        );
+        assert!(tail.is_none());
+    }
+
+    #[test]
+    fn predictions_fade_out_and_colour_by_speed() {
+        let history = straight_history(3);
+        let (built, _) = build(&history, prediction_steps(0, 3), 0.5);
+        let mut style = TrajectoryStyle {
+            colour: Some(Color::WHITE),
+            ..default()
+        };
+
+        let colours = prediction_colours(&built, &style, Color::BLACK, 0, 4);
+        assert_eq!(
+            colours.len(),
+            built.segments.len() * LineVertices::PER_SEGMENT
+        );
+        assert_eq!(
+            colours[0],
+            LinearRgba::new(1.0, 1.0, 1.0, style.opacity).to_f32_array()
+        );
+        // the end of the last segment, at step 2 of 4
+        assert_eq!(colours.last().unwrap()[3], style.opacity * 0.5);
+
+        style.colour_by_speed = true;
+        let colours = prediction_colours(&built, &style, Color::BLACK, 0, 4);
+        let [slowest, .., fastest] = colours[..] else {
+            unreachable!();
+        };
+        assert_eq!(
+            slowest[..3],
+            speed_colour(0.0).to_linear().to_f32_array()[..3]
+        );
+        assert_eq!(
+            fastest[..3],
+            speed_colour(1.0).to_linear().to_f32_array()[..3]
+        );
+    }
+
+    #[test]
+    fn trails_fade_out_towards_the_oldest_point() {
+        let trail = straight_history(3);
+        let steps = LineSteps {
+            kind: LineKind::Trail,
+            now: 3,
+            len: 3,
+        };
+        let (built, _) = build(&trail, steps, 0.5);
+        let trails = TrailSettings {
+            duration: 3.0 * TIME_STEP,
+            fade: FadeCurve::Linear,
+            ..default()
+        };
+
+        let colours = trail_colours(&built, &trails, Color::WHITE, 3);
+        // the oldest point first
+        let alphas = [colours[0][3], colours.last().unwrap()[3]];
+        assert!(alphas[0] < alphas[1]);
+        assert_eq!(alphas[1], trails.opacity);
+    }
+
+    #[test]
+    fn lines_are_rebuilt_when_zoomed_far_enough() {
+        let mut cache = LineCache::default();
+        let trails = TrailSettings::default();
+        assert!(cache.update(1.0, 10, Some(&trails)).rescaled);
+
+        let changes = cache.update(1.5, 10, Some(&trails));
+        assert!(!changes.rescaled && !changes.resized && !changes.trails_changed);
+        assert!(cache.update(2.5, 10, Some(&trails)).rescaled);
+        assert!(cache.update(2.5, 20, Some(&trails)).resized);
+    }
+}
@@ -0,0 +1,323 @@
+use bevy::{prelude::*, utils::HashSet};
+use bevy_egui::egui;
+
+const ROW_HEIGHT: f32 = 20.0;
+
+const COLUMN_WIDTH: f32 = 64.0;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(super) enum SortColumn {
+    #[default]
+    Name,
+    Mass,
+    Speed,
+    Distance,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(super) enum GroupBy {
+    #[default]
+    None,
+    Parent,
+    Tag,
+}
+
+impl GroupBy {
+    fn label(self) -> &'static str {
+        match self {
+            GroupBy::None => "None",
+            GroupBy::Parent => "Parent",
+            GroupBy::Tag => "Tag",
+        }
+    }
+}
+
+/// What the bodies of a group have in common
+#[derive(Clone, PartialEq, Eq, Hash)]
+enum GroupKey {
+    Parent(Option<Entity>),
+    Tag(Option<String>),
+}
+
+impl GroupKey {
+    fn of(group_by: GroupBy, row: &BodyRow) -> Option<Self> {
+        match group_by {
+            GroupBy::None => None,
+            GroupBy::Parent => Some(GroupKey::Parent(row.parent)),
+            GroupBy::Tag => Some(GroupKey::Tag(row.tag.clone())),
+        }
+    }
+
+    fn name<'a>(&'a self, rows: &'a [BodyRow]) -> Option<&'a str> {
+        match self {
+            GroupKey::Parent(parent) => parent
+                .and_then(|parent| rows.iter().find(|row| row.entity == parent))
+                .map(|row| row.name.as_str()),
+            GroupKey::Tag(tag) => tag.as_deref(),
+        }
+    }
+
+    fn label<'a>(&'a self, rows: &'a [BodyRow]) -> &'a str {
+        self.name(rows).unwrap_or(match self {
+            GroupKey::Parent(_) => "No parent",
+            GroupKey::Tag(_) => "No tag",
+        })
+    }
+}
+
+/// Filter, sorting and grouping of the body list
+#[derive(Default)]
+pub(super) struct BodyListState {
+    filter: String,
+    sort: SortColumn,
+    descending: bool,
+    group_by: GroupBy,
+    collapsed: HashSet<GroupKey>,
+}
+
+pub(super) struct BodyRow {
+    pub entity: Entity,
+    pub name: String,
+    pub mass: f32,
+    pub speed: f32,
+    /// Distance to the followed bodies, if any are followed
+    pub distance: Option<f32>,
+    pub parent: Option<Entity>,
+    pub tag: Option<String>,
+    pub trajectory_visible: bool,
+    pub is_selected: bool,
+}
+
+/// What the user did in the body list
+pub(super) enum BodyListAction {
+    /// Inspects and selects a body
+    Click(Entity),
+    Follow(Entity),
+    SetTrajectoryVisibility(Vec<Entity>, bool),
+}
+
+enum ListItem {
+    Group { key: GroupKey, bodies: Vec<usize> },
+    Body(usize),
+}
+
+fn format_quantity(value: f32) -> String {
+    if value != 0.0 && !(1e-2..1e4).contains(&value.abs()) {
+        format!("{value:.2e}")
+    } else {
+        format!("{value:.2}")
+    }
+}
+
+/// Filterable, sortable and groupable table of all bodies. Only the visible rows are laid out, so
+/// it stays fast with hundreds of bodies.
+pub(super) fn body_list(
+    ui: &mut egui::Ui,
+    state: &mut BodyListState,
+    rows: &[BodyRow],
+) -> Vec<BodyListAction> {
+    let mut actions = Vec::new();
+
+    ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
+        ui.label("Filter:");
+        ui.with_layout(egui::Layout::right_to_left(egui::Align::TOP), |ui| {
+            ui.add(egui::TextEdit::singleline(&mut state.filter).hint_text("Name"));
+        });
+    });
+
+    let filter = state.filter.to_lowercase();
+    let mut visible = (0..rows.len())
+        .filter(|i| rows[*i].name.to_lowercase().contains(&filter))
+        .collect::<Vec<_>>();
+
+    visible.sort_by(|a, b| {
+        let (a, b) = (&rows[*a], &rows[*b]);
+        let ordering = match state.sort {
+            SortColumn::Name => a.name.cmp(&b.name),
+            SortColumn::Mass => a.mass.total_cmp(&b.mass),
+            SortColumn::Speed => a.speed.total_cmp(&b.speed),
+            SortColumn::Distance => a
+                .distance
+                .unwrap_or(f32::INFINITY)
+                .total_cmp(&b.distance.unwrap_or(f32::INFINITY)),
+        };
+        if state.descending {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    });
+
+    ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
+        ui.label("Group by:");
+        egui::ComboBox::from_id_source("body_list_group_by")
+            .selected_text(state.group_by.label())
+            .show_ui(ui, |ui| {
+                for option in [GroupBy::None, GroupBy::Parent, GroupBy::Tag] {
+                    ui.selectable_value(&mut state.group_by, option, option.label());
+                }
+            });
+        ui.with_layout(egui::Layout::right_to_left(egui::Align::TOP), |ui| {
+            let entities = visible.iter().map(|i| rows[*i].entity).collect::<Vec<_>>();
+            if ui.button("Hide all").clicked() {
+                actions.push(BodyListAction::SetTrajectoryVisibility(
+                    entities.clone(),
+                    false,
+                ));
+            }
+            if ui.button("Show all").clicked() {
+                actions.push(BodyListAction::SetTrajectoryVisibility(entities, true));
+            }
+        });
+    });
+
+    let items = if state.group_by != GroupBy::None {
+        let mut groups: Vec<(GroupKey, Vec<usize>)> = Vec::new();
+        for i in &visible {
+            let Some(key) = GroupKey::of(state.group_by, &rows[*i]) else {
+                continue;
+            };
+            match groups.iter_mut().find(|(k, _)| *k == key) {
+                Some((_, bodies)) => bodies.push(*i),
+                None => groups.push((key, vec![*i])),
+            }
+        }
+        groups.sort_by(|(a, _), (b, _)| a.name(rows).cmp(&b.name(rows)));
+
+        groups
+            .into_iter()
+            .flat_map(|(key, bodies)| {
+                let collapsed = state.collapsed.contains(&key);
+                let body_items = if collapsed {
+                    Vec::new()
+                } else {
+                    bodies.iter().map(|i| ListItem::Body(*i)).collect()
+                };
+                std::iter::once(ListItem::Group { key, bodies }).chain(body_items)
+            })
+            .collect::<Vec<_>>()
+    } else {
+        visible.iter().map(|i| ListItem::Body(*i)).collect()
+    };
+
+    // header
+    ui.horizontal(|ui| {
+        let name_width = ui.available_width() - COLUMN_WIDTH * 3.0 - 40.0;
+        for (column, label, width) in [
+            (SortColumn::Name, "Name", name_width),
+            (SortColumn::Mass, "Mass", COLUMN_WIDTH),
+            (SortColumn::Speed, "Speed", COLUMN_WIDTH),
+            (SortColumn::Distance, "Distance", COLUMN_WIDTH),
+        ] {
+            let label = match (state.sort == column, state.descending) {
+                (true, false) => format!("{label} ⏶"),
+                (true, true) => format!("{label} ⏷"),
+                (false, _) => label.to_string(),
+            };
+            if ui
+                .add_sized(
+                    [width, ROW_HEIGHT],
+                    egui::SelectableLabel::new(false, label),
+                )
+                .clicked()
+            {
+                if state.sort == column {
+                    state.descending = !state.descending;
+                } else {
+                    state.sort = column;
+                    state.descending = false;
+                }
+            }
+        }
+    });
+    ui.separator();
+
+    egui::ScrollArea::vertical()
+        .max_height(300.0)
+        .auto_shrink([false, true])
+        .show_rows(ui, ROW_HEIGHT, items.len(), |ui, range| {
+            for item in &items[range] {
+                ui.horizontal(|ui| {
+                    let name_width = ui.available_width() - COLUMN_WIDTH * 3.0 - 40.0;
+
+                    match item {
+                        ListItem::Group { key, bodies } => {
+                            let collapsed = state.collapsed.contains(key);
+                            let group_name = key.label(rows);
+                            let icon = if collapsed { "⏵" } else { "⏷" };
+
+                            if ui
+                                .add_sized(
+                                    [name_width + COLUMN_WIDTH * 3.0, ROW_HEIGHT],
+                                    egui::SelectableLabel::new(
+                                        false,
+                                        egui::RichText::new(format!(
+                                            "{icon} {group_name} ({})",
+                                            bodies.len()
+                                        ))
+                                        .strong(),
+                                    ),
+                                )
+                                .clicked()
+                            {
+                                if collapsed {
+                                    state.collapsed.remove(key);
+                                } else {
+                                    state.collapsed.insert(key.clone());
+                                }
+                            }
+
+                            let mut all_visible =
+                                bodies.iter().all(|i| rows[*i].trajectory_visible);
+                            if ui
+                                .checkbox(&mut all_visible, "")
+                                .on_hover_text("Trajectories visible")
+                                .changed()
+                            {
+                                actions.push(BodyListAction::SetTrajectoryVisibility(
+                                    bodies.iter().map(|i| rows[*i].entity).collect(),
+                                    all_visible,
+                                ));
+                            }
+                        }
+                        ListItem::Body(i) => {
+                            let row = &rows[*i];
+
+                            let button = ui.add_sized(
+                                [name_width, ROW_HEIGHT],
+                                egui::Button::new(&row.name).selected(row.is_selected),
+                            );
+                            if button.clicked() {
+                                actions.push(BodyListAction::Click(row.entity));
+                            }
+                            if button.double_clicked() {
+                                actions.push(BodyListAction::Follow(row.entity));
+                            }
+
+                            for value in [
+                                format_quantity(row.mass),
+                                format_quantity(row.speed),
+                                row.distance.map_or("-".to_string(), format_quantity),
+                            ] {
+                                ui.add_sized([COLUMN_WIDTH, ROW_HEIGHT], egui::Label::new(value));
+                            }
+
+                            let mut visible = row.trajectory_visible;
+                            if ui
+                                .checkbox(&mut visible, "")
+                                .on_hover_text("Trajectory visible")
+                                .changed()
+                            {
+                                actions.push(BodyListAction::SetTrajectoryVisibility(
+                                    vec![row.entity],
+                                    visible,
+                                ));
+                            }
+                        }
+                    }
+                });
+            }
+        });
+
+    actions
+}
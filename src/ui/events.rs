@@ -0,0 +1,135 @@
+use std::fs::File;
+
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+
+use super::{Inspect, UiState};
+use crate::sim::{
+    events::{EventDetector, EventLog},
+    Follow, SimClock, Trajectory,
+};
+
+const ROW_HEIGHT: f32 = 18.0;
+
+pub(super) struct EventLogUi {
+    export_path: String,
+    status: Option<String>,
+}
+
+impl Default for EventLogUi {
+    fn default() -> Self {
+        Self {
+            export_path: "events.csv".to_string(),
+            status: None,
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub(super) fn event_log_window(
+    mut contexts: EguiContexts,
+    mut state: ResMut<UiState>,
+    mut ui_state: Local<EventLogUi>,
+    mut detector: ResMut<EventDetector>,
+    log: Res<EventLog>,
+    clock: Res<SimClock>,
+    bodies: Query<(), With<Trajectory>>,
+    focused: Query<Entity, With<Follow>>,
+    inspected: Query<Entity, With<Inspect>>,
+    mut cmds: Commands,
+) {
+    if !state.show_event_log {
+        return;
+    }
+
+    let ctx = contexts.ctx_mut();
+    let past = log.past(&clock);
+
+    let response = egui::Window::new("Event log")
+        .open(&mut state.show_event_log)
+        .default_width(350.0)
+        .show(ctx, |ui| {
+            egui::CollapsingHeader::new("Detection")
+                .default_open(false)
+                .show(ui, |ui| {
+                    ui.checkbox(&mut detector.enabled, "Enabled");
+                    ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
+                        ui.label("Close approach distance:");
+                        ui.with_layout(egui::Layout::right_to_left(egui::Align::TOP), |ui| {
+                            ui.add(
+                                egui::DragValue::new(&mut detector.close_approach_distance)
+                                    .range(0.0..=f32::MAX)
+                                    .speed(0.05),
+                            );
+                        });
+                    });
+                    ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
+                        ui.label("Escape distance:");
+                        ui.with_layout(egui::Layout::right_to_left(egui::Align::TOP), |ui| {
+                            ui.add(
+                                egui::DragValue::new(&mut detector.escape_distance)
+                                    .range(0.0..=f32::MAX)
+                                    .speed(0.5),
+                            );
+                        });
+                    });
+                });
+
+            ui.label(format!("Simulation time: {:.3}", clock.elapsed()));
+
+            ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
+                if ui.button("Export CSV").clicked() {
+                    let result = File::create(&ui_state.export_path)
+                        .and_then(|file| log.write_csv(&clock, file));
+                    ui_state.status = Some(match result {
+                        Ok(()) => format!("Exported {} events", past.len()),
+                        Err(err) => format!("Export failed: {err}"),
+                    });
+                }
+                ui.add(egui::TextEdit::singleline(&mut ui_state.export_path).desired_width(150.0));
+            });
+            if let Some(status) = &ui_state.status {
+                ui.label(status);
+            }
+
+            ui.separator();
+
+            egui::ScrollArea::vertical()
+                .auto_shrink([false, true])
+                .stick_to_bottom(true)
+                .show_rows(ui, ROW_HEIGHT, past.len(), |ui, rows| {
+                    for ev in &past[rows] {
+                        let text = format!(
+                            "{:>10.3}  {}: {} / {} ({:.2})",
+                            ev.time(),
+                            ev.kind.label(),
+                            ev.body_name,
+                            ev.other_name,
+                            ev.distance,
+                        );
+
+                        let row = ui.add(
+                            egui::Label::new(egui::RichText::new(text).monospace())
+                                .sense(egui::Sense::click())
+                                .truncate(),
+                        );
+
+                        // jump to the body by following it
+                        if row.clicked() && bodies.contains(ev.body) {
+                            for entity in focused.iter() {
+                                cmds.entity(entity).remove::<Follow>();
+                            }
+                            for entity in inspected.iter() {
+                                cmds.entity(entity).remove::<Inspect>();
+                            }
+                            cmds.entity(ev.body).insert((Follow, Inspect));
+                        }
+                    }
+                });
+        });
+
+    if let Some(response) = response {
+        state.is_active |= response.response.contains_pointer();
+        state.is_active |= ctx.dragging_something_else(response.response.id);
+    }
+}
@@ -0,0 +1,146 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+
+use super::{egui_colour, UiState};
+use crate::{
+    controls::SimCamera,
+    overlays::measure::MeasureOverlay,
+    sim::{Follow, Name, Radius, Trajectory},
+};
+
+const INDICATOR_MARGIN: f32 = 24.0;
+
+const ARROW_SIZE: f32 = 10.0;
+
+/// Shows the name of every visible body next to it and an arrow at the edge of the viewport
+/// pointing toward every body outside of it. Clicking an arrow follows its body.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub(super) fn body_labels(
+    mut contexts: EguiContexts,
+    mut state: ResMut<UiState>,
+    camera: Query<(&Camera, &OrthographicProjection, &GlobalTransform), With<SimCamera>>,
+    bodies: Query<(Entity, &Name, &Transform, &Radius, &Handle<ColorMaterial>), With<Trajectory>>,
+    materials: Res<Assets<ColorMaterial>>,
+    focused: Query<Entity, With<Follow>>,
+    measure_overlay: Res<MeasureOverlay>,
+    mut cmds: Commands,
+) {
+    if !state.show_labels && !state.show_offscreen_indicators {
+        return;
+    }
+
+    let ctx = contexts.ctx_mut();
+    let (cam, cam_projection, cam_transform) = camera.single();
+    let Some(viewport) = cam.logical_viewport_rect() else {
+        return;
+    };
+    let cam_pos = cam_transform.translation().xy();
+
+    for (entity, Name(name), transform, Radius(radius), material) in bodies.iter() {
+        let distance = transform.translation.xy().distance(cam_pos);
+        let colour = materials
+            .get(material)
+            .map_or(egui::Color32::WHITE, |material| egui_colour(material.color));
+
+        // the camera doesn't rotate, so this also works for positions outside of the viewport
+        let offset = (transform.translation.xy() - cam_pos) / cam_projection.scale;
+        let screen_space_pos = viewport.center() + Vec2::new(offset.x, -offset.y);
+
+        if viewport.contains(screen_space_pos) {
+            if !state.show_labels {
+                continue;
+            }
+
+            let screen_space_radius = radius / cam_projection.scale;
+            egui::Area::new(egui::Id::new(("body_label", entity)))
+                .fixed_pos([
+                    screen_space_pos.x + screen_space_radius + 4.0,
+                    screen_space_pos.y - screen_space_radius - 16.0,
+                ])
+                .order(egui::Order::Background)
+                .interactable(false)
+                .show(ctx, |ui| {
+                    ui.add(
+                        egui::Label::new(egui::RichText::new(name).color(colour))
+                            .wrap_mode(egui::TextWrapMode::Extend),
+                    );
+                });
+            continue;
+        }
+
+        if !state.show_offscreen_indicators {
+            continue;
+        }
+
+        // the point where the line from the centre of the viewport to the body leaves the
+        // viewport, moved inwards by the margin
+        let direction = (screen_space_pos - viewport.center()).normalize_or_zero();
+        let half_size = viewport.half_size() - INDICATOR_MARGIN;
+        let t = (half_size / direction.abs()).min_element();
+        let arrow_pos = viewport.center() + direction * t;
+
+        let response = egui::Area::new(egui::Id::new(("offscreen_indicator", entity)))
+            .fixed_pos([arrow_pos.x - ARROW_SIZE, arrow_pos.y - ARROW_SIZE])
+            .order(egui::Order::Background)
+            .show(ctx, |ui| {
+                let (rect, response) = ui
+                    .allocate_exact_size(egui::Vec2::splat(ARROW_SIZE * 2.0), egui::Sense::click());
+
+                let tip = rect.center() + egui::Vec2::new(direction.x, direction.y) * ARROW_SIZE;
+                let back = rect.center() - egui::Vec2::new(direction.x, direction.y) * ARROW_SIZE;
+                let side = egui::Vec2::new(-direction.y, direction.x) * ARROW_SIZE * 0.6;
+                ui.painter().add(egui::Shape::convex_polygon(
+                    vec![tip, back + side, back - side],
+                    colour,
+                    egui::Stroke::NONE,
+                ));
+
+                response
+            });
+
+        // the label goes on the side of the arrow facing the centre
+        let label_anchor = arrow_pos - direction * (ARROW_SIZE + 4.0);
+        egui::Area::new(egui::Id::new(("offscreen_label", entity)))
+            .fixed_pos([label_anchor.x, label_anchor.y])
+            .pivot(egui::Align2([
+                if direction.x > 0.5 {
+                    egui::Align::Max
+                } else if direction.x < -0.5 {
+                    egui::Align::Min
+                } else {
+                    egui::Align::Center
+                },
+                if direction.y > 0.5 {
+                    egui::Align::Max
+                } else if direction.y < -0.5 {
+                    egui::Align::Min
+                } else {
+                    egui::Align::Center
+                },
+            ]))
+            .order(egui::Order::Background)
+            .interactable(false)
+            .show(ctx, |ui| {
+                ui.add(
+                    egui::Label::new(
+                        egui::RichText::new(format!(
+                            "{name}\n{}",
+                            measure_overlay.format_distance(distance)
+                        ))
+                        .color(colour)
+                        .small(),
+                    )
+                    .wrap_mode(egui::TextWrapMode::Extend),
+                );
+            });
+
+        state.is_active |= response.response.contains_pointer();
+
+        if response.inner.clicked() {
+            for focused_entity in focused.iter() {
+                cmds.entity(focused_entity).remove::<Follow>();
+            }
+            cmds.entity(entity).insert(Follow);
+        }
+    }
+}
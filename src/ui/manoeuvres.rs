@@ -0,0 +1,286 @@
+use bevy::{color::palettes::css, prelude::*, window::PrimaryWindow};
+use bevy_egui::egui;
+
+use crate::{
+    controls::SimCamera,
+    input::{Action, ActionInput},
+    overlays::measure::cursor_world_pos,
+    sim::{
+        frame::ReferenceFrame,
+        manoeuvre::{self, ManoeuvreNode, Manoeuvres},
+        trajectory, ClearTrajectories, Mass, SimClock, Trajectory,
+    },
+};
+
+use super::{Inspect, UiState};
+
+const MARKER_SIZE: f32 = 6.0;
+
+const HANDLE_DISTANCE: f32 = 40.0;
+
+const PLACE_DISTANCE: f32 = 10.0;
+
+/// Δv per pixel a handle is dragged, relative to the body's speed at the node
+const DV_PER_PIXEL: f32 = 0.002;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum BurnAxis {
+    Prograde,
+    Radial,
+}
+
+struct HandleDrag {
+    node: usize,
+    axis: BurnAxis,
+    /// The retrograde or radial in handle
+    inverted: bool,
+    direction: Vec2,
+    start_cursor: Vec2,
+    start_value: f32,
+    sensitivity: f32,
+}
+
+/// Placing and dragging of manoeuvre nodes in the simulation view
+#[derive(Default)]
+pub(super) struct ManoeuvreTool {
+    /// The next click on this body's trajectory places a node
+    placing: Option<Entity>,
+    drag: Option<HandleDrag>,
+}
+
+/// Lists the manoeuvre nodes of the inspected body for editing
+pub(super) fn manoeuvre_section(
+    ui: &mut egui::Ui,
+    tool: &mut ManoeuvreTool,
+    entity: Entity,
+    manoeuvres: &mut Manoeuvres,
+) {
+    let mut removed = None;
+    let mut retimed = false;
+
+    for (i, node) in manoeuvres.0.iter_mut().enumerate() {
+        ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
+            ui.label(format!("Node {}:", i + 1));
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::TOP), |ui| {
+                if ui.small_button("x").on_hover_text("Remove node").clicked() {
+                    removed = Some(i);
+                }
+                retimed |= ui
+                    .add(
+                        egui::DragValue::new(&mut node.time)
+                            .range(0.0..=f64::MAX)
+                            .speed(0.05)
+                            .prefix("t = "),
+                    )
+                    .changed();
+            });
+        });
+        ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
+            ui.label("Δv:");
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::TOP), |ui| {
+                ui.add(
+                    egui::DragValue::new(&mut node.radial)
+                        .max_decimals(3)
+                        .speed(0.01)
+                        .prefix("radial "),
+                );
+                ui.add(
+                    egui::DragValue::new(&mut node.prograde)
+                        .max_decimals(3)
+                        .speed(0.01)
+                        .prefix("prograde "),
+                );
+            });
+        });
+    }
+
+    if let Some(i) = removed {
+        manoeuvres.0.remove(i);
+        tool.drag = None;
+    }
+    if retimed {
+        manoeuvres.0.sort_by(|a, b| a.time.total_cmp(&b.time));
+        tool.drag = None;
+    }
+
+    let mut placing = tool.placing == Some(entity);
+    if ui
+        .toggle_value(&mut placing, "Place on trajectory")
+        .on_hover_text("Click the predicted trajectory to add a node there")
+        .changed()
+    {
+        tool.placing = placing.then_some(entity);
+    }
+}
+
+/// Draws the manoeuvre nodes of the inspected body with prograde and radial handles, and lets
+/// new nodes be placed on its trajectory and the handles be dragged to change the Δv
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub(super) fn manoeuvre_nodes(
+    mut state: ResMut<UiState>,
+    input: ActionInput,
+    q_windows: Query<&Window, With<PrimaryWindow>>,
+    q_camera: Query<(&Camera, &GlobalTransform, &OrthographicProjection), With<SimCamera>>,
+    frame: Res<ReferenceFrame>,
+    clock: Res<SimClock>,
+    bodies: Query<(Entity, &Trajectory, &Mass)>,
+    mut inspected: Query<(Entity, Option<&mut Manoeuvres>), With<Inspect>>,
+    mut clear_traj_evw: EventWriter<ClearTrajectories>,
+    mut gizmos: Gizmos,
+    mut cmds: Commands,
+) {
+    let UiState {
+        manoeuvre_tool: tool,
+        is_active,
+        ..
+    } = &mut *state;
+
+    let Ok((entity, manoeuvres)) = inspected.get_single_mut() else {
+        tool.placing = None;
+        tool.drag = None;
+        return;
+    };
+    if input.just_pressed(Action::Cancel) {
+        tool.placing = None;
+    }
+    if !input.pressed(Action::Select) {
+        tool.drag = None;
+    }
+    let has_nodes = manoeuvres.as_ref().is_some_and(|m| !m.0.is_empty());
+    if !has_nodes && tool.placing != Some(entity) {
+        return;
+    }
+
+    let all = bodies
+        .iter()
+        .map(|(entity, trajectory, Mass(mass))| (entity, trajectory, *mass))
+        .collect::<Vec<_>>();
+    let Some(body) = all.iter().position(|(e, ..)| *e == entity) else {
+        return;
+    };
+    let Some(frames) = trajectory::frame_snapshots(&frame, &all) else {
+        return;
+    };
+    let trajectory = all[body].1;
+    let len = trajectory.iter().len().min(frames.len());
+    let point = |i: usize| frames[i].pos_in_frame(trajectory.get(i).unwrap().position);
+
+    let scale = q_camera.single().2.scale;
+    // windows in front of the view take the pointer, unless a handle is being dragged
+    let cursor_pos =
+        cursor_world_pos(&q_windows, &q_camera).filter(|_| !*is_active || tool.drag.is_some());
+
+    // only touch the component on edits
+    let mut nodes = manoeuvres.as_deref().cloned().unwrap_or_default();
+
+    for (n, node) in nodes.0.iter_mut().enumerate() {
+        let Some(i) = node
+            .step()
+            .checked_sub(clock.steps)
+            .map(|i| i as usize)
+            .filter(|i| *i < len)
+        else {
+            continue;
+        };
+        let Some(states) = all
+            .iter()
+            .map(|(_, trajectory, mass)| Some((trajectory.get(i)?, *mass)))
+            .collect::<Option<Vec<_>>>()
+        else {
+            continue;
+        };
+
+        let position = point(i);
+        let (prograde, radial) = manoeuvre::burn_axes(&states, body);
+        let in_frame = |axis: Vec3| frames[i].dir_in_frame(axis.xy()).normalize_or_zero();
+        let speed = states[body].0.velocity3().length().max(1e-3);
+
+        gizmos.circle_2d(position, MARKER_SIZE * scale, css::WHITE);
+
+        for (axis, inverted, colour) in [
+            (BurnAxis::Prograde, false, css::LIME),
+            (BurnAxis::Prograde, true, css::DARK_GREEN),
+            (BurnAxis::Radial, false, css::DEEP_SKY_BLUE),
+            (BurnAxis::Radial, true, css::STEEL_BLUE),
+        ] {
+            let sign = if inverted { -1.0 } else { 1.0 };
+            let direction = sign
+                * match axis {
+                    BurnAxis::Prograde => in_frame(prograde),
+                    BurnAxis::Radial => in_frame(radial),
+                };
+            if direction == Vec2::ZERO {
+                continue;
+            }
+            let handle = position + direction * HANDLE_DISTANCE * scale;
+            gizmos.line_2d(position, handle, colour.with_alpha(0.5));
+            gizmos.circle_2d(handle, MARKER_SIZE * scale, colour);
+
+            let Some(cursor_pos) = cursor_pos else {
+                continue;
+            };
+            let value = match axis {
+                BurnAxis::Prograde => &mut node.prograde,
+                BurnAxis::Radial => &mut node.radial,
+            };
+
+            if tool.drag.is_none()
+                && input.just_pressed(Action::Select)
+                && handle.distance(cursor_pos) < MARKER_SIZE * 1.5 * scale
+            {
+                tool.drag = Some(HandleDrag {
+                    node: n,
+                    axis,
+                    inverted,
+                    direction,
+                    start_cursor: cursor_pos,
+                    start_value: *value,
+                    sensitivity: speed * DV_PER_PIXEL / scale,
+                });
+            }
+
+            if let Some(drag) = tool
+                .drag
+                .as_ref()
+                .filter(|drag| drag.node == n && drag.axis == axis && drag.inverted == inverted)
+            {
+                // dragging a handle away from the node adds Δv in its direction
+                let dragged = (cursor_pos - drag.start_cursor).dot(drag.direction);
+                *value = drag.start_value + sign * dragged * drag.sensitivity;
+            }
+        }
+    }
+
+    if tool.drag.is_some() {
+        // keeps the camera from panning along
+        *is_active = true;
+    }
+
+    if let Some(cursor_pos) = cursor_pos.filter(|_| {
+        tool.placing == Some(entity) && tool.drag.is_none() && input.just_pressed(Action::Select)
+    }) {
+        // the current position is already past
+        let closest = (1..len)
+            .map(|i| (i, point(i).distance(cursor_pos)))
+            .min_by(|(_, a), (_, b)| a.total_cmp(b));
+
+        if let Some((i, _)) = closest.filter(|(_, distance)| *distance <= PLACE_DISTANCE * scale) {
+            nodes.insert(ManoeuvreNode {
+                time: SimClock::time_at(clock.steps + i as u64),
+                ..default()
+            });
+            tool.placing = None;
+            *is_active = true;
+        }
+    }
+
+    match manoeuvres {
+        Some(mut manoeuvres) if *manoeuvres != nodes => *manoeuvres = nodes,
+        Some(_) => return,
+        None if !nodes.0.is_empty() => {
+            cmds.entity(entity).insert(nodes);
+        }
+        None => return,
+    }
+    clear_traj_evw.send(ClearTrajectories);
+}
@@ -0,0 +1,78 @@
+use bevy::{prelude::*, window::PrimaryWindow};
+use bevy_egui::{egui, EguiContexts};
+
+use crate::{
+    controls::SimCamera,
+    overlays::measure::{self, MeasureOverlay, Ruler},
+    sim::{frame::CurrentFrame, Radius, Trajectory},
+};
+
+const SCALE_BAR_MAX_LENGTH: f32 = 150.0;
+
+/// Shows the scale bar in the bottom left corner and the length of the ruler next to it
+pub(super) fn measure_labels(
+    mut contexts: EguiContexts,
+    overlay: Res<MeasureOverlay>,
+    ruler: Res<Ruler>,
+    q_windows: Query<&Window, With<PrimaryWindow>>,
+    q_camera: Query<(&Camera, &GlobalTransform, &OrthographicProjection), With<SimCamera>>,
+    bodies: Query<(Entity, &Trajectory, &Transform, &Radius)>,
+    current_frame: Res<CurrentFrame>,
+) {
+    let ctx = contexts.ctx_mut();
+    let (cam, cam_transform, projection) = q_camera.single();
+
+    if overlay.show_scale_bar {
+        let length = overlay.round_distance(SCALE_BAR_MAX_LENGTH * projection.scale);
+        let screen_length = length / projection.scale;
+
+        egui::Area::new(egui::Id::new("scale_bar"))
+            .anchor(egui::Align2::LEFT_BOTTOM, [10.0, -40.0])
+            .order(egui::Order::Background)
+            .interactable(false)
+            .show(ctx, |ui| {
+                ui.label(overlay.format_distance(length));
+                let (rect, _) = ui
+                    .allocate_exact_size(egui::Vec2::new(screen_length, 8.0), egui::Sense::hover());
+                let stroke = egui::Stroke::new(2.0, egui::Color32::WHITE);
+                ui.painter()
+                    .line_segment([rect.left_bottom(), rect.right_bottom()], stroke);
+                ui.painter()
+                    .line_segment([rect.left_top(), rect.left_bottom()], stroke);
+                ui.painter()
+                    .line_segment([rect.right_top(), rect.right_bottom()], stroke);
+            });
+    }
+
+    let cursor = measure::cursor_world_pos(&q_windows, &q_camera);
+    let Some(measurement) = ruler.measure(cursor, &bodies, &current_frame) else {
+        return;
+    };
+    let Some(midpoint) = cam.world_to_viewport(
+        cam_transform,
+        ((measurement.start + measurement.end) / 2.0).extend(0.0),
+    ) else {
+        return;
+    };
+
+    egui::Area::new(egui::Id::new("ruler_label"))
+        .fixed_pos([midpoint.x + 8.0, midpoint.y + 8.0])
+        .order(egui::Order::Background)
+        .interactable(false)
+        .show(ctx, |ui| {
+            ui.add(
+                egui::Label::new(format!(
+                    "Distance: {}",
+                    overlay.format_distance(measurement.distance())
+                ))
+                .wrap_mode(egui::TextWrapMode::Extend),
+            );
+            ui.add(
+                egui::Label::new(format!(
+                    "Relative velocity: {}",
+                    overlay.format_speed(measurement.relative_velocity.length())
+                ))
+                .wrap_mode(egui::TextWrapMode::Extend),
+            );
+        });
+}
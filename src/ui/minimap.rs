@@ -0,0 +1,103 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+
+use super::{egui_colour, Inspect, UiState};
+use crate::{controls::SimCamera, sim::Trajectory};
+
+const MINIMAP_SIZE: f32 = 200.0;
+
+const DOT_RADIUS: f32 = 3.0;
+
+/// Overview of all bodies and the area the camera shows in the bottom right corner.
+/// Dragging pans the camera and clicking a body inspects it.
+#[allow(clippy::type_complexity)]
+pub(super) fn minimap(
+    mut contexts: EguiContexts,
+    mut state: ResMut<UiState>,
+    mut camera: Query<(&mut Transform, &OrthographicProjection), With<SimCamera>>,
+    bodies: Query<
+        (Entity, &Transform, &Handle<ColorMaterial>),
+        (With<Trajectory>, Without<SimCamera>),
+    >,
+    materials: Res<Assets<ColorMaterial>>,
+    inspected: Query<Entity, With<Inspect>>,
+    mut cmds: Commands,
+) {
+    if !state.show_minimap {
+        return;
+    }
+
+    let ctx = contexts.ctx_mut();
+    let (mut cam_transform, projection) = camera.single_mut();
+
+    let view = Rect::from_center_size(
+        cam_transform.translation.xy() + projection.area.center(),
+        projection.area.size(),
+    );
+    let bounds = bodies
+        .iter()
+        .fold(view, |bounds, (_, transform, _)| {
+            bounds.union_point(transform.translation.xy())
+        })
+        .inflate(projection.area.width().max(projection.area.height()) * 0.1);
+    let scale = MINIMAP_SIZE / bounds.width().max(bounds.height());
+
+    let response = egui::Area::new(egui::Id::new("minimap"))
+        .anchor(egui::Align2::RIGHT_BOTTOM, [-10.0, -10.0])
+        .order(egui::Order::Middle)
+        .show(ctx, |ui| {
+            egui::Frame::window(ui.style()).show(ui, |ui| {
+                let (rect, response) = ui.allocate_exact_size(
+                    egui::Vec2::splat(MINIMAP_SIZE),
+                    egui::Sense::click_and_drag(),
+                );
+                let to_minimap = |pos: Vec2| {
+                    let offset = (pos - bounds.center()) * scale;
+                    rect.center() + egui::Vec2::new(offset.x, -offset.y)
+                };
+
+                let painter = ui.painter_at(rect);
+                painter.rect_stroke(
+                    egui::Rect::from_two_pos(to_minimap(view.min), to_minimap(view.max)),
+                    0.0,
+                    egui::Stroke::new(1.0, egui::Color32::LIGHT_GRAY),
+                );
+
+                let mut clicked_body = None;
+
+                for (entity, transform, material) in bodies.iter() {
+                    let pos = to_minimap(transform.translation.xy());
+                    let colour = materials
+                        .get(material)
+                        .map_or(egui::Color32::WHITE, |material| egui_colour(material.color));
+                    painter.circle_filled(pos, DOT_RADIUS, colour);
+
+                    if response
+                        .interact_pointer_pos()
+                        .is_some_and(|pointer| pointer.distance(pos) <= DOT_RADIUS * 2.0)
+                    {
+                        clicked_body = Some(entity);
+                    }
+                }
+
+                if response.clicked() {
+                    if let Some(entity) = clicked_body {
+                        for entity in inspected.iter() {
+                            cmds.entity(entity).remove::<Inspect>();
+                        }
+                        cmds.entity(entity).insert(Inspect);
+                    }
+                }
+
+                if response.dragged() {
+                    let delta = response.drag_delta() / scale;
+                    cam_transform.translation += Vec3::new(delta.x, -delta.y, 0.0);
+                }
+
+                response
+            })
+        });
+
+    state.is_active |= response.response.contains_pointer();
+    state.is_active |= response.inner.inner.dragged();
+}
@@ -9,25 +9,82 @@ use bevy_egui::{
 
 use crate::{
     assets::system::System,
-    controls::SimCamera,
+    controls::{CameraBookmarks, CameraEvent, SimCamera},
+    input::{Action, ActionInput, InputMap},
+    overlays::measure::MeasureOverlay,
+    selection::{Selected, SelectionEvent},
     sim::{
-        ClearTrajectories, Follow, Hover, Mass, Name, Radius, SimData, SimSnapshot, SimState,
-        Trajectory, TrajectoryVisibility,
+        export::{ExportBodies, ExportStatus},
+        forces::{BodyForces, Burn, ForceSources},
+        frame::{CurrentFrame, ReferenceFrame},
+        manoeuvre::Manoeuvres,
+        orbit::{self, OrbitalElements},
+        trajectory::{FadeCurve, TrajectoryStyle},
+        ClearTrajectories, Follow, Hover, Mass, Name, Radius, SimClock, SimData, SimSnapshot,
+        SimState, Tag, Trajectory, TrajectoryVisibility, MAX_PREDICTION_HORIZON,
     },
+    view3d::{self, ViewMode},
     AppData, AppEvent, AppState,
 };
 
+mod body_list;
+mod events;
+mod labels;
+mod manoeuvres;
+mod measure;
+mod minimap;
+mod overlays;
+mod settings;
+
 #[derive(Resource)]
 pub struct UiState {
     show_inspector: bool,
+    show_overlays: bool,
+    show_event_log: bool,
+    show_labels: bool,
+    show_offscreen_indicators: bool,
+    show_minimap: bool,
+    show_settings: bool,
     is_active: bool,
+    selection_tools: SelectionTools,
+    body_list: body_list::BodyListState,
+    manoeuvre_tool: manoeuvres::ManoeuvreTool,
+    /// Body the inspected body's orbit is shown around, picked automatically if `None`
+    orbit_parent: Option<Entity>,
 }
 
 impl Default for UiState {
     fn default() -> Self {
         Self {
             show_inspector: true,
+            show_overlays: false,
+            show_event_log: false,
+            show_labels: false,
+            show_offscreen_indicators: true,
+            show_minimap: true,
+            show_settings: false,
             is_active: false,
+            selection_tools: SelectionTools::default(),
+            body_list: body_list::BodyListState::default(),
+            manoeuvre_tool: manoeuvres::ManoeuvreTool::default(),
+            orbit_parent: None,
+        }
+    }
+}
+
+/// Inputs of the group operations in the "Selection" section of the inspector
+struct SelectionTools {
+    translation: [f32; 2],
+    velocity: [f32; 2],
+    mass_factor: f32,
+}
+
+impl Default for SelectionTools {
+    fn default() -> Self {
+        Self {
+            translation: [0.0, 0.0],
+            velocity: [0.0, 0.0],
+            mass_factor: 1.0,
         }
     }
 }
@@ -58,12 +115,24 @@ fn register_images(mut contexts: EguiContexts, images: Res<Images>) {
     }
 }
 
+const DEFAULT_EXPORT_FOLDER: &str = "export";
+
+#[allow(clippy::too_many_arguments)]
 fn menu_bar(
     mut contexts: EguiContexts,
     app_data: Res<AppData>,
     mut ev_writer: EventWriter<AppEvent>,
     systems: Res<Assets<System>>,
     mut state: ResMut<UiState>,
+    mut bookmarks: ResMut<CameraBookmarks>,
+    mut camera_evw: EventWriter<CameraEvent>,
+    mut bookmark_name: Local<String>,
+    input_map: Res<InputMap>,
+    view_mode: Res<State<ViewMode>>,
+    mut next_view_mode: ResMut<NextState<ViewMode>>,
+    mut export_evw: EventWriter<ExportBodies>,
+    export_status: Res<ExportStatus>,
+    mut export_folder: Local<String>,
 ) {
     let ctx = contexts.ctx_mut();
 
@@ -80,11 +149,128 @@ fn menu_bar(
                 }
             });
 
+            egui::menu::menu_button(ui, "Export", |ui| {
+                ui.horizontal(|ui| {
+                    ui.add(
+                        egui::TextEdit::singleline(&mut *export_folder)
+                            .hint_text(DEFAULT_EXPORT_FOLDER)
+                            .desired_width(150.0),
+                    );
+                    if ui.button("Export bodies").clicked() {
+                        let folder = if export_folder.is_empty() {
+                            DEFAULT_EXPORT_FOLDER
+                        } else {
+                            export_folder.as_str()
+                        };
+                        export_evw.send(ExportBodies {
+                            folder: folder.into(),
+                        });
+                    }
+                });
+                ui.label(
+                    egui::RichText::new(
+                        "Writes every body to a .body.ron file and the constants to a \
+                         .system.ron file in the folder",
+                    )
+                    .small()
+                    .weak(),
+                );
+                if let Some(status) = &export_status.0 {
+                    ui.label(status);
+                }
+            });
+
             egui::menu::menu_button(ui, "View", |ui| {
                 egui::menu::menu_button(ui, "Windows", |ui| {
                     if ui.button("Inspector").clicked() {
                         state.show_inspector = !state.show_inspector;
                     }
+                    if ui.button("Overlays").clicked() {
+                        state.show_overlays = !state.show_overlays;
+                    }
+                    if ui.button("Event log").clicked() {
+                        state.show_event_log = !state.show_event_log;
+                    }
+                    if ui.button("Settings").clicked() {
+                        state.show_settings = !state.show_settings;
+                    }
+                });
+                ui.checkbox(&mut state.show_labels, "Body names");
+                ui.checkbox(
+                    &mut state.show_offscreen_indicators,
+                    "Off-screen indicators",
+                );
+                ui.checkbox(&mut state.show_minimap, "Minimap");
+
+                let mut three_d = *view_mode.get() == ViewMode::ThreeD;
+                if ui.checkbox(&mut three_d, "3D view").changed() {
+                    next_view_mode.set(if three_d {
+                        ViewMode::ThreeD
+                    } else {
+                        ViewMode::TwoD
+                    });
+                }
+
+                ui.separator();
+
+                let fit_all_label = match input_map.label(Action::FitAll) {
+                    Some(binding) => format!("Fit all bodies ({binding})"),
+                    None => "Fit all bodies".to_string(),
+                };
+                if ui.button(fit_all_label).clicked() {
+                    camera_evw.send(CameraEvent::FitAll);
+                    ui.close_menu();
+                }
+                egui::menu::menu_button(ui, "Bookmarks", |ui| {
+                    ui.horizontal(|ui| {
+                        ui.add(
+                            egui::TextEdit::singleline(&mut *bookmark_name)
+                                .hint_text("Name")
+                                .desired_width(100.0),
+                        );
+                        if ui.button("Save view").clicked() {
+                            let name = if bookmark_name.is_empty() {
+                                format!("View {}", bookmarks.0.len() + 1)
+                            } else {
+                                std::mem::take(&mut *bookmark_name)
+                            };
+                            camera_evw.send(CameraEvent::SaveBookmark { name, index: None });
+                        }
+                    });
+
+                    if !bookmarks.0.is_empty() {
+                        ui.separator();
+                    }
+
+                    let mut removed = None;
+                    for (index, bookmark) in bookmarks.0.iter().enumerate() {
+                        ui.horizontal(|ui| {
+                            let label = match input_map.label(Action::Bookmark(index)) {
+                                Some(binding) => format!("{} ({binding})", bookmark.name),
+                                None => bookmark.name.clone(),
+                            };
+                            if ui.button(label).clicked() {
+                                camera_evw.send(CameraEvent::RecallBookmark(index));
+                                ui.close_menu();
+                            }
+                            if ui.small_button("x").clicked() {
+                                removed = Some(index);
+                            }
+                        });
+                    }
+                    if let Some(index) = removed {
+                        bookmarks.0.remove(index);
+                    }
+
+                    if let Some(binding) = input_map.label(Action::SaveBookmark) {
+                        ui.label(
+                            egui::RichText::new(format!(
+                                "Hold {binding} with a bookmark key to save"
+                            ))
+                            .small()
+                            .weak(),
+                        );
+                    }
                 });
             });
         });
@@ -96,6 +282,11 @@ fn menu_bar(
 #[derive(Component)]
 pub struct Inspect;
 
+fn egui_colour(colour: Color) -> egui::Color32 {
+    let [r, g, b, _] = colour.to_srgba().to_u8_array();
+    egui::Color32::from_rgb(r, g, b)
+}
+
 #[allow(clippy::too_many_arguments, clippy::type_complexity)]
 fn inspector(
     mut contexts: EguiContexts,
@@ -108,13 +299,21 @@ fn inspector(
         &mut Trajectory,
         &Handle<ColorMaterial>,
         &mut Transform,
+        Option<&mut TrajectoryStyle>,
+        Option<&mut Manoeuvres>,
     )>,
     mut materials: ResMut<Assets<ColorMaterial>>,
     focused: Query<Entity, With<Follow>>,
     inspected: Query<Entity, With<Inspect>>,
+    selected: Query<Entity, With<Selected>>,
     mut state: ResMut<UiState>,
     mut clear_traj_evw: EventWriter<ClearTrajectories>,
+    mut selection_evw: EventWriter<SelectionEvent>,
     mut sim_data: ResMut<SimData>,
+    mut reference_frame: ResMut<ReferenceFrame>,
+    q_forces: Query<ForceSources>,
+    tags: Query<&Tag>,
+    input: ActionInput,
     mut cmds: Commands,
 ) {
     if !state.show_inspector {
@@ -150,54 +349,264 @@ fn inspector(
                         });
                     });
                     ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
-                        ui.label("Trajectory length:");
+                        if ui
+                            .checkbox(&mut sim_data.post_newtonian, "1PN correction, c:")
+                            .on_hover_text("Relativistic correction making orbits precess")
+                            .changed()
+                        {
+                            reset_trajectories = true;
+                        }
+                        ui.with_layout(egui::Layout::right_to_left(egui::Align::TOP), |ui| {
+                            ui.add_enabled_ui(sim_data.post_newtonian, |ui| {
+                                if ui
+                                    .add(
+                                        egui::DragValue::new(&mut sim_data.speed_of_light)
+                                            .range(1e-3..=f32::MAX)
+                                            .speed(1.0),
+                                    )
+                                    .changed()
+                                {
+                                    reset_trajectories = true;
+                                }
+                            });
+                        });
+                    });
+                    ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
+                        ui.label("Prediction horizon:");
                         ui.with_layout(egui::Layout::right_to_left(egui::Align::TOP), |ui| {
                             ui.add(
-                                egui::DragValue::new(&mut sim_data.trajectory_len)
-                                    .range(1..=usize::MAX),
+                                egui::DragValue::new(&mut sim_data.prediction_horizon)
+                                    .range(0.01..=MAX_PREDICTION_HORIZON)
+                                    .speed(0.1)
+                                    .suffix(" time"),
                             );
                         });
                     });
                     ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
                         ui.label("Speed:");
                         ui.with_layout(egui::Layout::right_to_left(egui::Align::TOP), |ui| {
-                            ui.add(egui::DragValue::new(&mut sim_data.speed).range(1..=usize::MAX));
+                            ui.add(
+                                egui::DragValue::new(&mut sim_data.speed)
+                                    .range(0.0..=f32::MAX)
+                                    .speed(0.01)
+                                    .suffix(" time/s"),
+                            );
                         });
                     });
                 });
 
+            let mut body_names = bodies
+                .iter()
+                .map(|(entity, name, ..)| (entity, name.0.clone()))
+                .collect::<Vec<_>>();
+            body_names.sort_by(|(_, a), (_, b)| a.cmp(b));
+
+            egui::CollapsingHeader::new("Reference frame")
+                .default_open(true)
+                .show(ui, |ui| {
+                    reference_frame_selector(
+                        ui,
+                        &mut reference_frame,
+                        &body_names,
+                        inspected.get_single().ok(),
+                    );
+                });
+
             egui::CollapsingHeader::new("Celestial Bodies")
                 .default_open(true)
                 .show(ui, |ui| {
-                    let mut sorted = bodies.iter_mut().collect::<Vec<_>>();
+                    let states = bodies
+                        .iter()
+                        .filter_map(|(entity, name, vis, mass, _, trajectory, ..)| {
+                            Some((entity, name.0.clone(), vis.0, mass.0, trajectory.front()?))
+                        })
+                        .collect::<Vec<_>>();
 
-                    sorted.sort_by(|(_, a, ..), (_, b, ..)| a.0.cmp(&b.0));
+                    // centre of mass of the followed bodies
+                    let (weighted, total_mass) = states
+                        .iter()
+                        .filter(|(entity, ..)| focused.contains(*entity))
+                        .fold(
+                            (Vec2::ZERO, 0.0),
+                            |(weighted, total), (.., mass, current)| {
+                                (weighted + current.position * *mass, total + mass)
+                            },
+                        );
+                    let focus = (total_mass > 0.0).then(|| weighted / total_mass);
 
-                    for (entity, ref mut name, ref mut vis, ..) in sorted.iter_mut() {
-                        ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
-                            let button = ui.button(name.0.clone());
-                            if button.clicked() {
+                    let parents = orbit::dominant_attractors(
+                        &states
+                            .iter()
+                            .map(|(.., mass, current)| (current.position3(), *mass))
+                            .collect::<Vec<_>>(),
+                    );
+
+                    let rows = states
+                        .iter()
+                        .zip(parents)
+                        .map(|((entity, name, visible, mass, current), parent)| {
+                            body_list::BodyRow {
+                                entity: *entity,
+                                name: name.clone(),
+                                mass: *mass,
+                                speed: current.velocity.length(),
+                                distance: focus.map(|focus| current.position.distance(focus)),
+                                parent: parent.map(|parent| states[parent].0),
+                                tag: tags.get(*entity).ok().map(|tag| tag.0.clone()),
+                                trajectory_visible: *visible,
+                                is_selected: selected.contains(*entity),
+                            }
+                        })
+                        .collect::<Vec<_>>();
+
+                    for action in body_list::body_list(ui, &mut state.body_list, &rows) {
+                        match action {
+                            body_list::BodyListAction::Click(entity) => {
                                 if let Ok(inspected_entity) = inspected_maybe {
                                     cmds.entity(inspected_entity).remove::<Inspect>();
                                 }
-                                cmds.entity(*entity).insert(Inspect);
-                            }
+                                cmds.entity(entity).insert(Inspect);
 
-                            if button.double_clicked() {
-                                if let Ok(focused_entity) = focused.get_single() {
+                                if !input.pressed(Action::AddToSelection) {
+                                    for selected_entity in selected.iter() {
+                                        cmds.entity(selected_entity).remove::<Selected>();
+                                    }
+                                    cmds.entity(entity).insert(Selected);
+                                } else if selected.contains(entity) {
+                                    cmds.entity(entity).remove::<Selected>();
+                                } else {
+                                    cmds.entity(entity).insert(Selected);
+                                }
+                            }
+                            body_list::BodyListAction::Follow(entity) => {
+                                for focused_entity in focused.iter() {
                                     cmds.entity(focused_entity).remove::<Follow>();
                                 }
-                                cmds.entity(*entity).insert(Follow);
+                                cmds.entity(entity).insert(Follow);
+                            }
+                            body_list::BodyListAction::SetTrajectoryVisibility(
+                                entities,
+                                visible,
+                            ) => {
+                                for entity in entities {
+                                    if let Ok((_, _, mut vis, ..)) = bodies.get_mut(entity) {
+                                        vis.0 = visible;
+                                    }
+                                }
                             }
+                        }
+                    }
+                });
+
+            let selected_count = selected.iter().len();
+
+            if selected_count > 1 {
+                let tools = &mut state.selection_tools;
 
+                egui::CollapsingHeader::new(format!("Selection ({selected_count} bodies)"))
+                    .default_open(true)
+                    .show(ui, |ui| {
+                        ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
+                            if ui.button("Translate").clicked() {
+                                selection_evw.send(SelectionEvent::Translate(Vec2::from_array(
+                                    tools.translation,
+                                )));
+                            }
                             ui.with_layout(egui::Layout::right_to_left(egui::Align::TOP), |ui| {
-                                ui.checkbox(&mut vis.0, "Trajectory Visible");
+                                ui.add(
+                                    egui::DragValue::new(&mut tools.translation[1])
+                                        .max_decimals(2)
+                                        .speed(0.05),
+                                );
+                                ui.add(
+                                    egui::DragValue::new(&mut tools.translation[0])
+                                        .max_decimals(2)
+                                        .speed(0.05),
+                                );
                             });
                         });
-                    }
-                });
+                        ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
+                            if ui.button("Add velocity").clicked() {
+                                selection_evw.send(SelectionEvent::AddVelocity(Vec2::from_array(
+                                    tools.velocity,
+                                )));
+                            }
+                            ui.with_layout(egui::Layout::right_to_left(egui::Align::TOP), |ui| {
+                                ui.add(
+                                    egui::DragValue::new(&mut tools.velocity[1])
+                                        .max_decimals(2)
+                                        .speed(0.05),
+                                );
+                                ui.add(
+                                    egui::DragValue::new(&mut tools.velocity[0])
+                                        .max_decimals(2)
+                                        .speed(0.05),
+                                );
+                            });
+                        });
+                        ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
+                            if ui.button("Scale mass").clicked() {
+                                selection_evw.send(SelectionEvent::ScaleMass(tools.mass_factor));
+                            }
+                            ui.with_layout(egui::Layout::right_to_left(egui::Align::TOP), |ui| {
+                                ui.add(
+                                    egui::DragValue::new(&mut tools.mass_factor)
+                                        .range(0.0..=f32::MAX)
+                                        .max_decimals(2)
+                                        .speed(0.01),
+                                );
+                            });
+                        });
+
+                        ui.separator();
+
+                        ui.horizontal_wrapped(|ui| {
+                            if ui.button("Show trajectories").clicked() {
+                                selection_evw.send(SelectionEvent::SetTrajectoryVisibility(true));
+                            }
+                            if ui.button("Hide trajectories").clicked() {
+                                selection_evw.send(SelectionEvent::SetTrajectoryVisibility(false));
+                            }
+                            if ui.button("Follow centre of mass").clicked() {
+                                selection_evw.send(SelectionEvent::FollowCentreOfMass);
+                            }
+                            if ui.button("Duplicate").clicked() {
+                                selection_evw.send(SelectionEvent::Duplicate);
+                            }
+                            if ui.button("Remove").clicked() {
+                                selection_evw.send(SelectionEvent::Delete);
+                            }
+                        });
+                    });
+            }
 
             if let Ok(inspected_entity) = inspected_maybe {
+                let states = bodies
+                    .iter()
+                    .map(|(entity, _, _, mass, _, traj, ..)| {
+                        (entity, traj.front().expect("Trajectory empty"), mass.0)
+                    })
+                    .collect::<Vec<_>>();
+
+                // the orbit is shown relative to the chosen parent or the dominant attractor
+                let parent = state
+                    .orbit_parent
+                    .filter(|parent| *parent != inspected_entity)
+                    .and_then(|parent| states.iter().find(|(entity, ..)| *entity == parent))
+                    .or_else(|| {
+                        let parents = orbit::dominant_attractors(
+                            &states
+                                .iter()
+                                .map(|(_, current, mass)| (current.position3(), *mass))
+                                .collect::<Vec<_>>(),
+                        );
+                        let inspected_index = states
+                            .iter()
+                            .position(|(entity, ..)| *entity == inspected_entity)?;
+                        parents[inspected_index].map(|parent| &states[parent])
+                    })
+                    .copied();
+
                 let (
                     entity,
                     mut name,
@@ -207,11 +616,16 @@ fn inspector(
                     mut trajectory,
                     mat_handle,
                     mut transform,
+                    style,
+                    manoeuvres,
                 ) = bodies.get_mut(inspected_entity).unwrap();
 
                 let SimSnapshot {
                     ref mut position,
                     ref mut velocity,
+                    z,
+                    velocity_z,
+                    ..
                 } = trajectory.front_mut().expect("Trajectory empty");
 
                 ui.separator();
@@ -282,6 +696,47 @@ fn inspector(
                             });
                         });
 
+                        if let Some(parent) = parent {
+                            egui::CollapsingHeader::new("Orbit")
+                                .default_open(true)
+                                .show(ui, |ui| {
+                                    let mut parent_entity = parent.0;
+                                    ui.with_layout(
+                                        egui::Layout::left_to_right(egui::Align::TOP),
+                                        |ui| {
+                                            if ui.small_button("Auto").clicked() {
+                                                state.orbit_parent = None;
+                                            }
+                                            body_selector(
+                                                ui,
+                                                "Parent:",
+                                                &mut parent_entity,
+                                                &body_names,
+                                            );
+                                        },
+                                    );
+                                    if parent_entity != parent.0 {
+                                        state.orbit_parent = Some(parent_entity);
+                                    }
+
+                                    // the elements only describe orbits in the plane
+                                    if *z != parent.1.z || *velocity_z != parent.1.velocity_z {
+                                        ui.label("Inclined orbits can't be edited");
+                                    } else {
+                                        orbit_section(
+                                            ui,
+                                            parent,
+                                            sim_data.gravitational_const * (parent.2 + mass_tmp),
+                                            sim_data
+                                                .post_newtonian
+                                                .then_some(sim_data.speed_of_light),
+                                            &mut pos_tmp,
+                                            &mut vel_tmp,
+                                        );
+                                    }
+                                });
+                        }
+
                         ui.separator();
 
                         ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
@@ -314,6 +769,60 @@ fn inspector(
                         }
                     });
 
+                // only touch the style on edits, the trajectory line is rebuilt on every change
+                let mut style_tmp = style.as_deref().cloned().unwrap_or_default();
+                egui::CollapsingHeader::new("Trajectory style")
+                    .default_open(false)
+                    .show(ui, |ui| {
+                        trajectory_style_section(ui, &mut style_tmp);
+                    });
+                match style {
+                    Some(mut style) if *style != style_tmp => *style = style_tmp,
+                    Some(_) => (),
+                    None => {
+                        cmds.entity(entity).insert(style_tmp);
+                    }
+                }
+
+                let mut manoeuvres_tmp = manoeuvres.as_deref().cloned().unwrap_or_default();
+                egui::CollapsingHeader::new("Manoeuvres")
+                    .default_open(false)
+                    .show(ui, |ui| {
+                        manoeuvres::manoeuvre_section(
+                            ui,
+                            &mut state.manoeuvre_tool,
+                            entity,
+                            &mut manoeuvres_tmp,
+                        );
+                    });
+                match manoeuvres {
+                    Some(mut manoeuvres) if *manoeuvres != manoeuvres_tmp => {
+                        *manoeuvres = manoeuvres_tmp;
+                        reset_trajectories = true;
+                    }
+                    Some(_) => {}
+                    None if !manoeuvres_tmp.0.is_empty() => {
+                        cmds.entity(entity).insert(manoeuvres_tmp);
+                        reset_trajectories = true;
+                    }
+                    None => {}
+                }
+
+                let forces = q_forces
+                    .get(entity)
+                    .map(|sources| BodyForces::from_sources(&sources))
+                    .unwrap_or_default();
+                let mut forces_tmp = forces.clone();
+                egui::CollapsingHeader::new("Forces")
+                    .default_open(false)
+                    .show(ui, |ui| {
+                        forces_section(ui, &mut forces_tmp);
+                    });
+                if forces_tmp != forces {
+                    forces_tmp.apply(&mut cmds.entity(entity));
+                    reset_trajectories = true;
+                }
+
                 let pos_tmp = Vec2::from_array(pos_tmp);
                 let vel_tmp = Vec2::from_array(vel_tmp);
 
@@ -321,7 +830,6 @@ fn inspector(
                     mass.0 = mass_tmp;
                     *position = pos_tmp;
                     *velocity = vel_tmp;
-                    transform.translation = pos_tmp.extend(0.0);
                     reset_trajectories = true;
                 }
             }
@@ -335,13 +843,386 @@ fn inspector(
     }
 }
 
+fn trajectory_style_section(ui: &mut egui::Ui, style: &mut TrajectoryStyle) {
+    ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
+        ui.label("Width:");
+        ui.with_layout(egui::Layout::right_to_left(egui::Align::TOP), |ui| {
+            ui.add(
+                egui::DragValue::new(&mut style.width)
+                    .range(0.5..=20.0)
+                    .speed(0.1)
+                    .suffix(" px"),
+            );
+        });
+    });
+
+    ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
+        let mut custom_colour = style.colour.is_some();
+        ui.checkbox(&mut custom_colour, "Custom colour");
+        ui.with_layout(egui::Layout::right_to_left(egui::Align::TOP), |ui| {
+            if !custom_colour {
+                style.colour = None;
+                return;
+            }
+
+            let mut colour = style
+                .colour
+                .unwrap_or(Color::WHITE)
+                .to_srgba()
+                .to_f32_array();
+            if ui
+                .color_edit_button_rgba_unmultiplied(&mut colour)
+                .changed()
+                || style.colour.is_none()
+            {
+                style.colour = Some(Color::srgba(colour[0], colour[1], colour[2], colour[3]));
+            }
+        });
+    });
+
+    ui.checkbox(&mut style.dashed, "Dashed");
+    ui.checkbox(&mut style.colour_by_speed, "Colour by speed");
+
+    ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
+        ui.label("Fade:");
+        ui.with_layout(egui::Layout::right_to_left(egui::Align::TOP), |ui| {
+            egui::ComboBox::from_id_source("trajectory_fade")
+                .selected_text(style.fade.label())
+                .show_ui(ui, |ui| {
+                    for option in [FadeCurve::Linear, FadeCurve::Quadratic, FadeCurve::None] {
+                        ui.selectable_value(&mut style.fade, option, option.label());
+                    }
+                });
+        });
+    });
+    ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
+        ui.label("Opacity:");
+        ui.with_layout(egui::Layout::right_to_left(egui::Align::TOP), |ui| {
+            ui.add(egui::Slider::new(&mut style.opacity, 0.0..=1.0));
+        });
+    });
+}
+
+fn optional_section<T: Default>(
+    ui: &mut egui::Ui,
+    label: &str,
+    component: &mut Option<T>,
+    add_contents: impl FnOnce(&mut egui::Ui, &mut T),
+) {
+    let mut enabled = component.is_some();
+    if ui.checkbox(&mut enabled, label).changed() {
+        *component = enabled.then(T::default);
+    }
+    if let Some(component) = component {
+        ui.indent(label, |ui| add_contents(ui, component));
+    }
+}
+
+fn drag_vec3(ui: &mut egui::Ui, vector: &mut Vec3) {
+    for value in [&mut vector.z, &mut vector.y, &mut vector.x] {
+        ui.add(egui::DragValue::new(value).max_decimals(3).speed(0.01));
+    }
+}
+
+fn forces_section(ui: &mut egui::Ui, forces: &mut BodyForces) {
+    optional_section(ui, "Thrust", &mut forces.thrust, |ui, thrust| {
+        ui.checkbox(&mut thrust.relative_to_velocity, "Relative to velocity")
+            .on_hover_text("Forces are given as prograde, lateral and normal components");
+        ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
+            ui.label("Constant:");
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::TOP), |ui| {
+                drag_vec3(ui, &mut thrust.constant);
+            });
+        });
+
+        let mut removed = None;
+        for (i, burn) in thrust.burns.iter_mut().enumerate() {
+            ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
+                ui.label(format!("Burn {}:", i + 1));
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::TOP), |ui| {
+                    if ui.small_button("x").on_hover_text("Remove burn").clicked() {
+                        removed = Some(i);
+                    }
+                    ui.add(
+                        egui::DragValue::new(&mut burn.duration)
+                            .range(0.0..=f32::MAX)
+                            .speed(0.01)
+                            .prefix("for "),
+                    );
+                    ui.add(
+                        egui::DragValue::new(&mut burn.start)
+                            .range(0.0..=f32::MAX)
+                            .speed(0.1)
+                            .prefix("t = "),
+                    );
+                });
+            });
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::TOP), |ui| {
+                drag_vec3(ui, &mut burn.force);
+            });
+        }
+        if let Some(i) = removed {
+            thrust.burns.remove(i);
+        }
+        if ui.button("Add burn").clicked() {
+            thrust.burns.push(Burn {
+                duration: 1.0,
+                ..default()
+            });
+        }
+    });
+
+    optional_section(ui, "Drag", &mut forces.drag, |ui, drag| {
+        ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
+            ui.label("Coefficient × area:");
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::TOP), |ui| {
+                ui.add(
+                    egui::DragValue::new(&mut drag.coefficient)
+                        .range(0.0..=f32::MAX)
+                        .speed(0.01),
+                );
+            });
+        });
+    });
+
+    optional_section(
+        ui,
+        "Atmosphere",
+        &mut forces.atmosphere,
+        |ui, atmosphere| {
+            ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
+                ui.label("Surface density:");
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::TOP), |ui| {
+                    ui.add(
+                        egui::DragValue::new(&mut atmosphere.surface_density)
+                            .range(0.0..=f32::MAX)
+                            .speed(0.01),
+                    );
+                });
+            });
+            ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
+                ui.label("Scale height:");
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::TOP), |ui| {
+                    ui.add(
+                        egui::DragValue::new(&mut atmosphere.scale_height)
+                            .range(1e-3..=f32::MAX)
+                            .speed(0.01),
+                    );
+                });
+            });
+        },
+    );
+
+    optional_section(
+        ui,
+        "Radiation pressure",
+        &mut forces.radiation_pressure,
+        |ui, radiation| {
+            ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
+                ui.label("Area:");
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::TOP), |ui| {
+                    ui.add(
+                        egui::DragValue::new(&mut radiation.area)
+                            .range(0.0..=f32::MAX)
+                            .speed(0.01),
+                    );
+                });
+            });
+        },
+    );
+
+    optional_section(ui, "Star", &mut forces.star, |ui, star| {
+        ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
+            ui.label("Radiation pressure:");
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::TOP), |ui| {
+                ui.add(
+                    egui::DragValue::new(&mut star.pressure)
+                        .range(0.0..=f32::MAX)
+                        .speed(0.1),
+                );
+            });
+        });
+    });
+}
+
+const TRUE_ANOMALY_MARGIN: f32 = 1e-3;
+
+fn orbit_section(
+    ui: &mut egui::Ui,
+    (_, parent, _): (Entity, SimSnapshot, f32),
+    mu: f32,
+    speed_of_light: Option<f32>,
+    position: &mut [f32; 2],
+    velocity: &mut [f32; 2],
+) {
+    let rel_pos = Vec2::from_array(*position) - parent.position;
+    let rel_vel = Vec2::from_array(*velocity) - parent.velocity;
+
+    let Some(mut elements) = OrbitalElements::from_state(rel_pos, rel_vel, mu) else {
+        ui.label("Degenerate orbit");
+        ui.label(format!(
+            "Specific energy: {:.3}",
+            orbit::specific_energy(rel_pos.extend(0.0), rel_vel.extend(0.0), mu)
+        ));
+        return;
+    };
+    let original = elements;
+    // editing must not turn a bound orbit into an unbound one or the other way around,
+    // as the semi-major axis changes its sign in between
+    let bound = elements.is_bound();
+
+    ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
+        ui.label("Semi-major axis:");
+        ui.with_layout(egui::Layout::right_to_left(egui::Align::TOP), |ui| {
+            ui.add(
+                egui::DragValue::new(&mut elements.semi_major_axis)
+                    .range(if bound {
+                        1e-3..=f32::MAX
+                    } else {
+                        f32::MIN..=-1e-3
+                    })
+                    .max_decimals(3)
+                    .speed(0.05),
+            );
+        });
+    });
+    ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
+        ui.label("Eccentricity:");
+        ui.with_layout(egui::Layout::right_to_left(egui::Align::TOP), |ui| {
+            ui.add(
+                egui::DragValue::new(&mut elements.eccentricity)
+                    .range(if bound { 0.0..=0.999 } else { 1.001..=f32::MAX })
+                    .max_decimals(3)
+                    .speed(0.005),
+            );
+        });
+    });
+    ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
+        ui.label("Argument of periapsis:");
+        ui.with_layout(egui::Layout::right_to_left(egui::Align::TOP), |ui| {
+            ui.drag_angle(&mut elements.argument_of_periapsis);
+        });
+    });
+    ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
+        ui.label("True anomaly:");
+        ui.with_layout(egui::Layout::right_to_left(egui::Align::TOP), |ui| {
+            ui.drag_angle(&mut elements.true_anomaly);
+        });
+    });
+    // past the limit an unbound orbit would be at infinity, or on the other branch of the hyperbola
+    if let Some(limit) = elements
+        .true_anomaly_limit()
+        .filter(|_| elements != original)
+    {
+        let limit = limit - TRUE_ANOMALY_MARGIN;
+        elements.true_anomaly = elements.true_anomaly.clamp(-limit, limit);
+    }
+
+    ui.label(format!("Periapsis: {:.3}", elements.periapsis()));
+    if let Some(apoapsis) = elements.apoapsis() {
+        ui.label(format!("Apoapsis: {apoapsis:.3}"));
+    }
+    if let Some(period) = elements.period(mu) {
+        ui.label(format!("Period: {period:.3}"));
+    }
+    if let Some(speed_of_light) = speed_of_light.filter(|_| bound) {
+        ui.label(format!(
+            "Periapsis precession: {:.4}°/orbit",
+            orbit::relativistic_precession(mu, &elements, speed_of_light).to_degrees()
+        ));
+    }
+    ui.label(format!(
+        "Specific energy: {:.3}",
+        elements.specific_energy(mu)
+    ));
+
+    if elements != original {
+        let (rel_pos, rel_vel) = elements.to_state(mu);
+        *position = (parent.position + rel_pos).to_array();
+        *velocity = (parent.velocity + rel_vel).to_array();
+    }
+}
+
+fn reference_frame_selector(
+    ui: &mut egui::Ui,
+    frame: &mut ReferenceFrame,
+    bodies: &[(Entity, String)],
+    inspected: Option<Entity>,
+) {
+    // bodies a newly chosen frame is centred on
+    let first = inspected.or(bodies.first().map(|(entity, _)| *entity));
+    let second = bodies
+        .iter()
+        .map(|(entity, _)| *entity)
+        .find(|entity| Some(*entity) != first);
+
+    ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
+        ui.label("Frame:");
+        ui.with_layout(egui::Layout::right_to_left(egui::Align::TOP), |ui| {
+            egui::ComboBox::from_id_source("reference_frame")
+                .selected_text(frame.label())
+                .show_ui(ui, |ui| {
+                    for option in [ReferenceFrame::Inertial, ReferenceFrame::Barycentric] {
+                        ui.selectable_value(frame, option, option.label());
+                    }
+
+                    let is_body = matches!(frame, ReferenceFrame::Body(_));
+                    if let Some(body) = first {
+                        if ui.selectable_label(is_body, "Body-centred").clicked() && !is_body {
+                            *frame = ReferenceFrame::Body(body);
+                        }
+                    }
+
+                    let is_co_rotating = matches!(frame, ReferenceFrame::CoRotating { .. });
+                    if let (Some(primary), Some(secondary)) = (first, second) {
+                        if ui.selectable_label(is_co_rotating, "Co-rotating").clicked()
+                            && !is_co_rotating
+                        {
+                            *frame = ReferenceFrame::CoRotating { primary, secondary };
+                        }
+                    }
+                });
+        });
+    });
+
+    match frame {
+        ReferenceFrame::Body(body) => body_selector(ui, "Body:", body, bodies),
+        ReferenceFrame::CoRotating { primary, secondary } => {
+            body_selector(ui, "Primary:", primary, bodies);
+            body_selector(ui, "Secondary:", secondary, bodies);
+        }
+        _ => (),
+    }
+}
+
+fn body_selector(ui: &mut egui::Ui, label: &str, body: &mut Entity, bodies: &[(Entity, String)]) {
+    let selected_name = bodies
+        .iter()
+        .find(|(entity, _)| entity == body)
+        .map_or("", |(_, name)| name.as_str());
+
+    ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
+        ui.label(label);
+        ui.with_layout(egui::Layout::right_to_left(egui::Align::TOP), |ui| {
+            egui::ComboBox::from_id_source(label)
+                .selected_text(selected_name)
+                .show_ui(ui, |ui| {
+                    for (entity, name) in bodies {
+                        ui.selectable_value(body, *entity, name.as_str());
+                    }
+                });
+        });
+    });
+}
+
 #[allow(clippy::too_many_arguments, clippy::type_complexity)]
 fn hover_indicator(
     camera: Query<(&Camera, &OrthographicProjection, &GlobalTransform), With<SimCamera>>,
     bodies: Query<
         (Entity, &Trajectory, &Transform, &Radius, Option<&Inspect>),
-        Or<(With<Follow>, With<Hover>, With<Inspect>)>,
+        Or<(With<Follow>, With<Hover>, With<Inspect>, With<Selected>)>,
     >,
+    current_frame: Res<CurrentFrame>,
     images: Res<Images>,
     mut contexts: EguiContexts,
 ) {
@@ -352,7 +1233,13 @@ fn hover_indicator(
     let (cam, cam_projection, cam_transform) = camera.single();
 
     for (entity, trajectory, transform, Radius(radius), maybe_inspect) in bodies.iter() {
-        let SimSnapshot { velocity, position } = trajectory.front().unwrap();
+        let SimSnapshot {
+            velocity, position, ..
+        } = trajectory.front().unwrap();
+        let (position, velocity) = (
+            current_frame.0.pos_in_frame(position),
+            current_frame.0.vel_in_frame(position, velocity),
+        );
 
         let scale = f32::max(radius * cam_projection.scale, *radius / 6.0);
 
@@ -475,10 +1362,28 @@ fn hover_indicator(
     }
 }
 
+/// Inputs of the "run for" and "run until" controls
+struct RunControls {
+    duration: f64,
+    until: f64,
+}
+
+impl Default for RunControls {
+    fn default() -> Self {
+        Self {
+            duration: 1.0,
+            until: 10.0,
+        }
+    }
+}
+
 fn sim_controls(
     mut contexts: EguiContexts,
     sim_state: Res<State<SimState>>,
     mut next_sim_state: ResMut<NextState<SimState>>,
+    mut clock: ResMut<SimClock>,
+    measure: Res<MeasureOverlay>,
+    mut run_controls: Local<RunControls>,
     images: Res<Images>,
 ) {
     let pause_icon = contexts
@@ -540,6 +1445,67 @@ fn sim_controls(
                         },
                     );
                 });
+
+                cols[2].with_layout(egui::Layout::top_down(egui::Align::Max), |ui| {
+                    let elapsed = clock.elapsed();
+                    ui.label(egui::RichText::new(format!("t = {elapsed:.3}")).monospace());
+                    if let Some(calendar) = measure.format_time(elapsed) {
+                        ui.label(egui::RichText::new(calendar).monospace());
+                    }
+
+                    let mut run_to = None;
+                    ui.horizontal(|ui| {
+                        ui.with_layout(egui::Layout::right_to_left(egui::Align::TOP), |ui| {
+                            if ui.button("Run").clicked() {
+                                run_to = Some(elapsed + run_controls.duration);
+                            }
+                            ui.add(
+                                egui::DragValue::new(&mut run_controls.duration)
+                                    .range(0.0..=f64::MAX)
+                                    .speed(0.05),
+                            );
+                            ui.label("Run for Δt =");
+                        });
+                    });
+                    ui.horizontal(|ui| {
+                        ui.with_layout(egui::Layout::right_to_left(egui::Align::TOP), |ui| {
+                            if ui
+                                .add_enabled(run_controls.until > elapsed, egui::Button::new("Run"))
+                                .on_disabled_hover_text(format!(
+                                    "Choose a time after the current t = {elapsed:.3}"
+                                ))
+                                .clicked()
+                            {
+                                run_to = Some(run_controls.until);
+                            }
+                            ui.add(
+                                egui::DragValue::new(&mut run_controls.until)
+                                    .range(0.0..=f64::MAX)
+                                    .speed(0.05),
+                            );
+                            ui.label("Run until t =");
+                        });
+                    });
+
+                    if let Some(time) = run_to {
+                        clock.stop_at = Some(SimClock::step_at(time));
+                        next_sim_state.set(SimState::Playing);
+                    }
+
+                    if let Some(stop_at) = clock.stop_at {
+                        ui.horizontal(|ui| {
+                            ui.with_layout(egui::Layout::right_to_left(egui::Align::TOP), |ui| {
+                                if ui.small_button("x").on_hover_text("Cancel").clicked() {
+                                    clock.stop_at = None;
+                                }
+                                ui.label(format!(
+                                    "Pausing at t = {:.3}",
+                                    SimClock::time_at(stop_at)
+                                ));
+                            });
+                        });
+                    }
+                });
             });
         });
 }
@@ -574,12 +1540,27 @@ impl Plugin for UiPlugin {
             .add_systems(
                 Update,
                 (
-                    hover_indicator,
+                    hover_indicator.run_if(view3d::in_2d),
+                    measure::measure_labels
+                        .run_if(in_state(AppState::Simulating))
+                        .run_if(view3d::in_2d),
                     (
                         reset_state,
                         menu_bar,
+                        labels::body_labels
+                            .run_if(in_state(AppState::Simulating))
+                            .run_if(view3d::in_2d),
+                        minimap::minimap
+                            .run_if(in_state(AppState::Simulating))
+                            .run_if(view3d::in_2d),
                         inspector.run_if(in_state(AppState::Simulating)),
+                        overlays::overlays_window.run_if(in_state(AppState::Simulating)),
+                        events::event_log_window.run_if(in_state(AppState::Simulating)),
+                        settings::settings_window,
                         sim_controls.run_if(in_state(AppState::Simulating)),
+                        manoeuvres::manoeuvre_nodes
+                            .run_if(in_state(AppState::Simulating))
+                            .run_if(view3d::in_2d),
                     )
                         .chain(),
                 )
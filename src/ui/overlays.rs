@@ -0,0 +1,338 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+
+use super::{body_selector, egui_colour, UiState};
+use crate::{
+    input::{Action, InputMap},
+    overlays::{
+        conics::ConicOverlay,
+        lagrange::{self, LagrangeOverlay},
+        measure::{MeasureOverlay, Ruler},
+        potential::{ColourMap, FieldQuantity, PotentialOverlay},
+        vectors::VectorOverlay,
+    },
+    sim::{
+        trajectory::{FadeCurve, TrailSettings},
+        Mass, Name, Radius, Trajectory,
+    },
+};
+
+#[allow(clippy::too_many_arguments)]
+pub(super) fn overlays_window(
+    mut contexts: EguiContexts,
+    mut state: ResMut<UiState>,
+    mut lagrange_overlay: ResMut<LagrangeOverlay>,
+    mut conic_overlay: ResMut<ConicOverlay>,
+    mut vector_overlay: ResMut<VectorOverlay>,
+    mut potential_overlay: ResMut<PotentialOverlay>,
+    mut measure_overlay: ResMut<MeasureOverlay>,
+    mut ruler: ResMut<Ruler>,
+    mut trails: ResMut<TrailSettings>,
+    input_map: Res<InputMap>,
+    names: Query<(Entity, &Name)>,
+    bodies: Query<(&Trajectory, &Mass, &Radius)>,
+) {
+    if !state.show_overlays {
+        return;
+    }
+
+    let ctx = contexts.ctx_mut();
+
+    let mut body_names = names
+        .iter()
+        .map(|(entity, name)| (entity, name.0.clone()))
+        .collect::<Vec<_>>();
+    body_names.sort_by(|(_, a), (_, b)| a.cmp(b));
+
+    let response = egui::Window::new("Overlays")
+        .open(&mut state.show_overlays)
+        .default_width(250.0)
+        .show(ctx, |ui| {
+            egui::CollapsingHeader::new("Scale")
+                .default_open(true)
+                .show(ui, |ui| {
+                    measure_section(ui, &mut measure_overlay, &mut ruler, &input_map);
+                });
+            egui::CollapsingHeader::new("Trails")
+                .default_open(true)
+                .show(ui, |ui| {
+                    trails_section(ui, &mut trails);
+                });
+            egui::CollapsingHeader::new("Conic orbits")
+                .default_open(true)
+                .show(ui, |ui| {
+                    ui.checkbox(&mut conic_overlay.enabled, "Enabled");
+                    ui.label(
+                        "Two-body orbit of every body with a visible trajectory around its \
+                         dominant attractor",
+                    );
+                });
+            egui::CollapsingHeader::new("Vectors")
+                .default_open(true)
+                .show(ui, |ui| {
+                    vectors_section(ui, &mut vector_overlay);
+                });
+            egui::CollapsingHeader::new("Potential field")
+                .default_open(true)
+                .show(ui, |ui| {
+                    potential_section(ui, &mut potential_overlay);
+                });
+            egui::CollapsingHeader::new("Lagrange points")
+                .default_open(true)
+                .show(ui, |ui| {
+                    lagrange_section(ui, &mut lagrange_overlay, &body_names, &bodies);
+                });
+        });
+
+    if let Some(response) = response {
+        state.is_active |= response.response.contains_pointer();
+        state.is_active |= ctx.dragging_something_else(response.response.id);
+    }
+}
+
+fn measure_section(
+    ui: &mut egui::Ui,
+    overlay: &mut MeasureOverlay,
+    ruler: &mut Ruler,
+    input_map: &InputMap,
+) {
+    ui.checkbox(&mut overlay.show_grid, "Grid");
+    ui.checkbox(&mut overlay.show_scale_bar, "Scale bar");
+    ui.checkbox(&mut overlay.physical_units, "Physical units");
+
+    if overlay.physical_units {
+        ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
+            ui.label("Metres per unit");
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::TOP), |ui| {
+                ui.add(
+                    egui::DragValue::new(&mut overlay.metres_per_unit)
+                        .range(f32::MIN_POSITIVE..=f32::MAX)
+                        .speed(0.01),
+                );
+            });
+        });
+        ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
+            ui.label("Seconds per unit");
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::TOP), |ui| {
+                ui.add(
+                    egui::DragValue::new(&mut overlay.seconds_per_unit)
+                        .range(f32::MIN_POSITIVE..=f32::MAX)
+                        .speed(0.01),
+                );
+            });
+        });
+    }
+
+    ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
+        let label = match input_map.label(Action::ToggleRuler) {
+            Some(binding) => format!("Ruler ({binding})"),
+            None => "Ruler".to_string(),
+        };
+        ui.checkbox(&mut ruler.active, label);
+        ui.with_layout(egui::Layout::right_to_left(egui::Align::TOP), |ui| {
+            if ui
+                .add_enabled(ruler.start.is_some(), egui::Button::new("Clear"))
+                .clicked()
+            {
+                ruler.clear();
+            }
+        });
+    });
+    if ruler.active {
+        ui.label("Click two points or bodies to measure the distance between them");
+    }
+}
+
+fn trails_section(ui: &mut egui::Ui, trails: &mut TrailSettings) {
+    ui.checkbox(&mut trails.enabled, "Enabled");
+
+    ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
+        ui.label("Duration");
+        ui.with_layout(egui::Layout::right_to_left(egui::Align::TOP), |ui| {
+            ui.add(
+                egui::DragValue::new(&mut trails.duration)
+                    .range(0.0..=TrailSettings::MAX_DURATION)
+                    .speed(0.1)
+                    .suffix(" time"),
+            );
+        });
+    });
+    ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
+        ui.label("Fade");
+        ui.with_layout(egui::Layout::right_to_left(egui::Align::TOP), |ui| {
+            egui::ComboBox::from_id_source("trail_fade")
+                .selected_text(trails.fade.label())
+                .show_ui(ui, |ui| {
+                    for option in [FadeCurve::Linear, FadeCurve::Quadratic, FadeCurve::None] {
+                        ui.selectable_value(&mut trails.fade, option, option.label());
+                    }
+                });
+        });
+    });
+    ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
+        ui.checkbox(&mut trails.body_colour, "Body colour");
+        ui.with_layout(egui::Layout::right_to_left(egui::Align::TOP), |ui| {
+            let mut colour = egui_colour(trails.colour);
+            if ui
+                .add_enabled_ui(!trails.body_colour, |ui| {
+                    ui.color_edit_button_srgba(&mut colour)
+                })
+                .inner
+                .changed()
+            {
+                trails.colour = Color::srgb_u8(colour.r(), colour.g(), colour.b());
+            }
+        });
+    });
+    ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
+        ui.label("Opacity");
+        ui.with_layout(egui::Layout::right_to_left(egui::Align::TOP), |ui| {
+            ui.add(egui::Slider::new(&mut trails.opacity, 0.0..=1.0));
+        });
+    });
+}
+
+fn vectors_section(ui: &mut egui::Ui, overlay: &mut VectorOverlay) {
+    let VectorOverlay {
+        show_velocity,
+        show_acceleration,
+        show_forces,
+        show_sources,
+        velocity_scale,
+        acceleration_scale,
+        force_scale,
+        log_scale,
+    } = overlay;
+
+    for (show, scale, label) in [
+        (show_velocity, velocity_scale, "Velocity"),
+        (show_acceleration, acceleration_scale, "Acceleration"),
+        (show_forces, force_scale, "Forces by body"),
+    ] {
+        ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
+            ui.checkbox(show, label);
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::TOP), |ui| {
+                ui.add(
+                    egui::DragValue::new(scale)
+                        .range(0.0..=f32::MAX)
+                        .speed(0.01)
+                        .prefix("scale: "),
+                );
+            });
+        });
+    }
+
+    ui.checkbox(show_sources, "Forces by source");
+    ui.checkbox(log_scale, "Logarithmic length");
+}
+
+fn potential_section(ui: &mut egui::Ui, overlay: &mut PotentialOverlay) {
+    ui.checkbox(&mut overlay.enabled, "Enabled");
+
+    ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
+        ui.label("Quantity");
+        ui.with_layout(egui::Layout::right_to_left(egui::Align::TOP), |ui| {
+            egui::ComboBox::from_id_source("field_quantity")
+                .selected_text(overlay.quantity.label())
+                .show_ui(ui, |ui| {
+                    for option in [FieldQuantity::Potential, FieldQuantity::FieldStrength] {
+                        ui.selectable_value(&mut overlay.quantity, option, option.label());
+                    }
+                });
+        });
+    });
+    ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
+        ui.label("Colour map");
+        ui.with_layout(egui::Layout::right_to_left(egui::Align::TOP), |ui| {
+            egui::ComboBox::from_id_source("colour_map")
+                .selected_text(overlay.colour_map.label())
+                .show_ui(ui, |ui| {
+                    for option in [ColourMap::Viridis, ColourMap::Inferno, ColourMap::Greyscale] {
+                        ui.selectable_value(&mut overlay.colour_map, option, option.label());
+                    }
+                });
+        });
+    });
+    ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
+        ui.checkbox(&mut overlay.contours, "Contours");
+        ui.with_layout(egui::Layout::right_to_left(egui::Align::TOP), |ui| {
+            ui.add_enabled(
+                overlay.contours,
+                egui::DragValue::new(&mut overlay.contour_count).range(1..=64),
+            );
+        });
+    });
+    ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
+        ui.label("Resolution");
+        ui.with_layout(egui::Layout::right_to_left(egui::Align::TOP), |ui| {
+            ui.add(egui::DragValue::new(&mut overlay.resolution).range(16..=512));
+        });
+    });
+    ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
+        ui.label("Opacity");
+        ui.with_layout(egui::Layout::right_to_left(egui::Align::TOP), |ui| {
+            ui.add(egui::Slider::new(&mut overlay.opacity, 0.0..=1.0));
+        });
+    });
+    ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
+        ui.label("Update interval");
+        ui.with_layout(egui::Layout::right_to_left(egui::Align::TOP), |ui| {
+            ui.add(
+                egui::DragValue::new(&mut overlay.update_interval)
+                    .range(0.0..=5.0)
+                    .speed(0.01)
+                    .suffix(" s"),
+            );
+        });
+    });
+}
+
+fn lagrange_section(
+    ui: &mut egui::Ui,
+    overlay: &mut LagrangeOverlay,
+    body_names: &[(Entity, String)],
+    bodies: &Query<(&Trajectory, &Mass, &Radius)>,
+) {
+    ui.checkbox(&mut overlay.enabled, "Enabled");
+
+    let pair_exists = overlay
+        .pair
+        .is_some_and(|(primary, secondary)| bodies.contains(primary) && bodies.contains(secondary));
+
+    if !pair_exists {
+        // default to the two heaviest bodies
+        let mut by_mass = body_names
+            .iter()
+            .filter_map(|(entity, _)| bodies.get(*entity).ok().map(|(_, m, _)| (*entity, m.0)))
+            .collect::<Vec<_>>();
+        by_mass.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+
+        overlay.pair = match by_mass.as_slice() {
+            [(primary, _), (secondary, _), ..] => Some((*primary, *secondary)),
+            _ => None,
+        };
+    }
+
+    let Some((primary, secondary)) = &mut overlay.pair else {
+        ui.label("Needs at least two bodies");
+        return;
+    };
+
+    body_selector(ui, "Primary:", primary, body_names);
+    body_selector(ui, "Secondary:", secondary, body_names);
+
+    ui.separator();
+
+    ui.checkbox(&mut overlay.show_points, "Lagrange points");
+    ui.checkbox(&mut overlay.show_hill_sphere, "Hill sphere");
+    ui.checkbox(&mut overlay.show_roche_limit, "Roche limit");
+
+    if let Some(geometry) = lagrange::pair_geometry(overlay, bodies) {
+        ui.separator();
+        ui.label(format!("Hill radius: {:.3}", geometry.hill_radius));
+        ui.label(format!("Roche limit: {:.3}", geometry.roche_limit));
+        if overlay.show_points {
+            ui.label("Click a Lagrange point to place a test body there");
+        }
+    }
+}
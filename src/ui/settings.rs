@@ -0,0 +1,270 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+
+use super::UiState;
+use crate::{
+    input::{Action, Binding, GamepadMap, InputButton, InputMap},
+    settings::{self, Preferences},
+    sim::{SimData, MAX_PREDICTION_HORIZON},
+};
+
+const MODIFIER_KEYS: [KeyCode; 8] = [
+    KeyCode::ControlLeft,
+    KeyCode::ControlRight,
+    KeyCode::ShiftLeft,
+    KeyCode::ShiftRight,
+    KeyCode::AltLeft,
+    KeyCode::AltRight,
+    KeyCode::SuperLeft,
+    KeyCode::SuperRight,
+];
+
+/// The binding that is currently being changed
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(super) enum Rebinding {
+    /// Replaces the key or mouse binding of the index, or adds one
+    Keyboard(Action, Option<usize>),
+    Gamepad(Action),
+}
+
+fn captured_binding(
+    ctx: &egui::Context,
+    action: Action,
+    kb: &ButtonInput<KeyCode>,
+    mouse: &ButtonInput<MouseButton>,
+) -> Option<Binding> {
+    let button = kb
+        .get_just_pressed()
+        .find(|key| action.is_modifier() || !MODIFIER_KEYS.contains(key))
+        .map(|key| InputButton::Key(*key))
+        .or_else(|| {
+            // clicks on the UI are meant for the UI
+            (!ctx.is_pointer_over_area())
+                .then(|| mouse.get_just_pressed().next())
+                .flatten()
+                .map(|button| InputButton::Mouse(*button))
+        })?;
+
+    Some(Binding {
+        button,
+        ctrl: !action.is_modifier()
+            && kb.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]),
+    })
+}
+
+fn toggle_rebinding(rebinding: &mut Option<Rebinding>, toggled: Rebinding) {
+    *rebinding = if *rebinding == Some(toggled) {
+        None
+    } else {
+        Some(toggled)
+    };
+}
+
+#[allow(clippy::too_many_arguments)]
+pub(super) fn settings_window(
+    mut contexts: EguiContexts,
+    mut state: ResMut<UiState>,
+    mut input_map: ResMut<InputMap>,
+    mut gamepad_map: ResMut<GamepadMap>,
+    mut preferences: ResMut<Preferences>,
+    sim_data: Res<SimData>,
+    kb: Res<ButtonInput<KeyCode>>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    gamepad_buttons: Res<ButtonInput<GamepadButton>>,
+    mut rebinding: Local<Option<Rebinding>>,
+    mut edited: Local<Option<Preferences>>,
+) {
+    if !state.show_settings {
+        *rebinding = None;
+        *edited = None;
+        return;
+    }
+
+    let ctx = contexts.ctx_mut();
+
+    let was_rebinding = rebinding.is_some();
+    match *rebinding {
+        Some(Rebinding::Keyboard(action, index)) => {
+            if let Some(binding) = captured_binding(ctx, action, &kb, &mouse) {
+                let bindings = input_map.0.entry(action).or_default();
+                match index.filter(|index| *index < bindings.len()) {
+                    Some(index) => bindings[index] = binding,
+                    None if !bindings.contains(&binding) => bindings.push(binding),
+                    None => (),
+                }
+                *rebinding = None;
+            }
+        }
+        Some(Rebinding::Gamepad(action)) => {
+            if let Some(button) = gamepad_buttons.get_just_pressed().next() {
+                gamepad_map.0.insert(action, button.button_type);
+                *rebinding = None;
+            }
+        }
+        None => (),
+    }
+    if was_rebinding {
+        // the key press is only meant for the bindings editor
+        state.is_active = true;
+    }
+
+    let response = egui::Window::new("Settings")
+        .open(&mut state.show_settings)
+        .default_width(300.0)
+        .show(ctx, |ui| {
+            // every change of the preferences is written to the settings file, so edits are kept
+            // here until the value is no longer being dragged or typed in
+            let mut draft = *edited.get_or_insert(*preferences);
+            let mut editing = false;
+
+            egui::CollapsingHeader::new("Defaults")
+                .default_open(true)
+                .show(ui, |ui| {
+                    ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
+                        ui.label("Prediction horizon");
+                        ui.with_layout(egui::Layout::right_to_left(egui::Align::TOP), |ui| {
+                            let response = ui.add(
+                                egui::DragValue::new(&mut draft.prediction_horizon)
+                                    .range(0.01..=MAX_PREDICTION_HORIZON)
+                                    .speed(0.1)
+                                    .suffix(" time"),
+                            );
+                            editing |= response.dragged() || response.has_focus();
+                        });
+                    });
+                    ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
+                        ui.label("Time scale");
+                        ui.with_layout(egui::Layout::right_to_left(egui::Align::TOP), |ui| {
+                            let response = ui.add(
+                                egui::DragValue::new(&mut draft.time_scale)
+                                    .range(0.0..=100.0)
+                                    .speed(0.01)
+                                    .suffix(" time/s"),
+                            );
+                            editing |= response.dragged() || response.has_focus();
+                        });
+                    });
+                    if ui.button("Use current simulation arguments").clicked() {
+                        draft.prediction_horizon = sim_data.prediction_horizon;
+                        draft.time_scale = sim_data.speed;
+                    }
+                });
+
+            if editing {
+                *edited = Some(draft);
+            } else {
+                *edited = None;
+                if draft != *preferences {
+                    *preferences = draft;
+                }
+            }
+
+            egui::CollapsingHeader::new("Bindings")
+                .default_open(true)
+                .show(ui, |ui| {
+                    egui::Grid::new("bindings")
+                        .num_columns(3)
+                        .striped(true)
+                        .show(ui, |ui| {
+                            for action in Action::ALL {
+                                ui.label(action.label());
+
+                                ui.horizontal(|ui| {
+                                    let mut removed = None;
+                                    let bindings =
+                                        input_map.0.get(&action).map_or(&[][..], Vec::as_slice);
+                                    for (index, binding) in bindings.iter().enumerate() {
+                                        let rebound = Rebinding::Keyboard(action, Some(index));
+                                        let label = if *rebinding == Some(rebound) {
+                                            "Press a key...".to_string()
+                                        } else {
+                                            binding.label()
+                                        };
+                                        let response =
+                                            ui.button(label).on_hover_text("Right click to remove");
+                                        if response.clicked() {
+                                            toggle_rebinding(&mut rebinding, rebound);
+                                        }
+                                        if response.secondary_clicked() {
+                                            removed = Some(index);
+                                        }
+                                    }
+
+                                    let added = Rebinding::Keyboard(action, None);
+                                    if *rebinding == Some(added) {
+                                        if ui.button("Press a key...").clicked() {
+                                            *rebinding = None;
+                                        }
+                                    } else {
+                                        if bindings.is_empty() {
+                                            ui.weak("Unbound");
+                                        }
+                                        if ui
+                                            .small_button("+")
+                                            .on_hover_text("Add a binding")
+                                            .clicked()
+                                        {
+                                            *rebinding = Some(added);
+                                        }
+                                    }
+
+                                    if let Some(index) = removed {
+                                        if let Some(bindings) = input_map.0.get_mut(&action) {
+                                            bindings.remove(index);
+                                        }
+                                        *rebinding = None;
+                                    }
+
+                                    let conflicts = input_map.conflicts(action);
+                                    if !conflicts.is_empty() {
+                                        ui.colored_label(egui::Color32::YELLOW, "⚠").on_hover_text(
+                                            format!(
+                                                "Also bound to {}",
+                                                conflicts
+                                                    .iter()
+                                                    .map(|other| other.label())
+                                                    .collect::<Vec<_>>()
+                                                    .join(", ")
+                                            ),
+                                        );
+                                    }
+                                });
+
+                                let label = if *rebinding == Some(Rebinding::Gamepad(action)) {
+                                    "Press a button...".to_string()
+                                } else {
+                                    gamepad_map
+                                        .0
+                                        .get(&action)
+                                        .map_or("Unbound".to_string(), |button| {
+                                            format!("{button:?}")
+                                        })
+                                };
+                                if ui.button(label).clicked() {
+                                    toggle_rebinding(&mut rebinding, Rebinding::Gamepad(action));
+                                }
+                                ui.end_row();
+                            }
+                        });
+
+                    if ui.button("Reset to defaults").clicked() {
+                        *input_map = InputMap::default();
+                        *gamepad_map = GamepadMap::default();
+                        *rebinding = None;
+                    }
+                });
+
+            if let Some(path) = settings::settings_path() {
+                ui.label(
+                    egui::RichText::new(format!("Saved to {}", path.display()))
+                        .small()
+                        .weak(),
+                );
+            }
+        });
+
+    if let Some(response) = response {
+        state.is_active |= response.response.contains_pointer();
+        state.is_active |= ctx.dragging_something_else(response.response.id);
+    }
+}
@@ -0,0 +1,536 @@
+use std::f32::consts::FRAC_PI_2;
+
+use bevy::{
+    input::mouse::{MouseMotion, MouseScrollUnit, MouseWheel},
+    pbr::{MaterialPipeline, MaterialPipelineKey, NotShadowCaster},
+    prelude::*,
+    render::{
+        mesh::MeshVertexBufferLayoutRef,
+        render_resource::{
+            AsBindGroup, RenderPipelineDescriptor, ShaderRef, SpecializedMeshPipelineError,
+        },
+        view::NoFrustumCulling,
+    },
+    utils::HashSet,
+};
+
+use crate::{
+    controls::{ControlSystemSet, SimCamera},
+    input::{Action, ActionInput},
+    sim::{
+        self,
+        frame::{CurrentFrame, ReferenceFrame},
+        trajectory::{
+            self, BuiltLine, LineCache, LineChanges, LineKind, LineShape, LineSteps, LineUpdate,
+            TrajectoryStyle,
+        },
+        Follow, Mass, SimClock, SimData, SimSystemSet, Trajectory, TrajectoryVisibility,
+    },
+    ui, utils, AppState,
+};
+
+/// in radians per pixel
+const ROTATE_SPEED: f32 = 0.005;
+
+/// Fraction of the camera distance panned per second with the pan keys
+const PAN_SPEED: f32 = 0.8;
+
+const LINE_SHADER: &str = "shaders/trajectory_line_3d.wgsl";
+
+/// Whether the simulation is shown from above with the 2D camera or in perspective
+#[derive(States, Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ViewMode {
+    #[default]
+    TwoD,
+    ThreeD,
+}
+
+/// Perspective camera orbiting the followed bodies, or a free point if nothing is followed
+#[derive(Component)]
+pub struct OrbitCamera {
+    /// Offset of the point the camera orbits from the followed bodies
+    pub offset: Vec3,
+    /// Angle around the z axis
+    pub yaw: f32,
+    /// Angle above the orbital plane
+    pub pitch: f32,
+    pub distance: f32,
+}
+
+impl Default for OrbitCamera {
+    fn default() -> Self {
+        Self {
+            offset: Vec3::ZERO,
+            yaw: -FRAC_PI_2,
+            pitch: 1.0,
+            distance: 50.0,
+        }
+    }
+}
+
+impl OrbitCamera {
+    fn transform(&self, focus: Vec3) -> Transform {
+        let direction = Vec3::new(
+            self.pitch.cos() * self.yaw.cos(),
+            self.pitch.cos() * self.yaw.sin(),
+            self.pitch.sin(),
+        );
+        Transform::from_translation(focus + direction * self.distance).looking_at(focus, Vec3::Z)
+    }
+}
+
+/// Sphere showing `body` in the 3D view
+#[derive(Component)]
+struct BodySphere {
+    body: Entity,
+}
+
+/// Predicted trajectory of `body` in the 3D view
+#[derive(Component)]
+struct TrajectoryLine3d {
+    body: Entity,
+    built_colour: Option<Color>,
+    built: Option<BuiltLine>,
+}
+
+/// Draws the trajectory lines with the same width on screen however far away they are. The
+/// vertices lie on the middle of the line and the shader moves them out to its edges.
+#[derive(Asset, TypePath, AsBindGroup, Clone, PartialEq)]
+struct LineMaterial3d {
+    /// World units per pixel at the point the camera orbits, the dashes are measured in it
+    #[uniform(0)]
+    scale: f32,
+    #[uniform(1)]
+    pixel_ratio: f32,
+}
+
+impl Material for LineMaterial3d {
+    fn vertex_shader() -> ShaderRef {
+        LINE_SHADER.into()
+    }
+
+    fn fragment_shader() -> ShaderRef {
+        LINE_SHADER.into()
+    }
+
+    fn alpha_mode(&self) -> AlphaMode {
+        AlphaMode::Blend
+    }
+
+    fn specialize(
+        _pipeline: &MaterialPipeline<Self>,
+        descriptor: &mut RenderPipelineDescriptor,
+        layout: &MeshVertexBufferLayoutRef,
+        _key: MaterialPipelineKey<Self>,
+    ) -> Result<(), SpecializedMeshPipelineError> {
+        let vertex_layout = layout.0.get_layout(&[
+            Mesh::ATTRIBUTE_POSITION.at_shader_location(0),
+            Mesh::ATTRIBUTE_COLOR.at_shader_location(1),
+            trajectory::ATTRIBUTE_LINE_SEGMENT.at_shader_location(2),
+            trajectory::ATTRIBUTE_LINE_DASH.at_shader_location(3),
+        ])?;
+        descriptor.vertex.buffers = vec![vertex_layout];
+        // the camera sees the lines from both sides
+        descriptor.primitive.cull_mode = None;
+        Ok(())
+    }
+}
+
+fn setup(mut cmds: Commands) {
+    cmds.spawn((
+        Camera3dBundle {
+            camera: Camera {
+                is_active: false,
+                order: 1,
+                ..default()
+            },
+            ..default()
+        },
+        OrbitCamera::default(),
+    ))
+    .with_children(|camera| {
+        // lights the bodies from the viewer's side
+        camera.spawn(DirectionalLightBundle::default());
+    });
+}
+
+/// Places the orbit camera above what the 2D camera showed when switching to it
+fn switch_camera(
+    view_mode: Res<State<ViewMode>>,
+    mut q_2d: Query<(&mut Camera, &Transform, &OrthographicProjection), With<SimCamera>>,
+    mut q_3d: Query<(&mut Camera, &mut OrbitCamera, &Projection), Without<SimCamera>>,
+    focused: Query<(), With<Follow>>,
+) {
+    let (mut camera_2d, transform_2d, projection_2d) = q_2d.single_mut();
+    let (mut camera_3d, mut orbit, projection_3d) = q_3d.single_mut();
+
+    let three_d = *view_mode.get() == ViewMode::ThreeD;
+    camera_2d.is_active = !three_d;
+    camera_3d.is_active = three_d;
+
+    if three_d {
+        let fov = match projection_3d {
+            Projection::Perspective(perspective) => perspective.fov,
+            Projection::Orthographic(_) => FRAC_PI_2,
+        };
+        orbit.distance = projection_2d.area.height() / 2.0 / (fov / 2.0).tan();
+        // the 2D camera is placed relative to the followed bodies as well
+        orbit.offset = if focused.is_empty() {
+            (transform_2d.translation.xy() + projection_2d.area.center()).extend(0.0)
+        } else {
+            Vec3::ZERO
+        };
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn orbit_camera_controller(
+    mut q_camera: Query<(&mut Transform, &mut OrbitCamera, &mut Projection)>,
+    focused: Query<(&Trajectory, &Mass), With<Follow>>,
+    current_frame: Res<CurrentFrame>,
+    input: ActionInput,
+    mut motion: EventReader<MouseMotion>,
+    mut wheel: EventReader<MouseWheel>,
+    ui_active: Res<ui::UiState>,
+    time: Res<Time>,
+) {
+    let (mut transform, mut orbit, mut projection) = q_camera.single_mut();
+
+    let motion = motion.read().map(|ev| ev.delta).sum::<Vec2>();
+    let scroll = wheel
+        .read()
+        .map(|ev| match ev.unit {
+            MouseScrollUnit::Line => ev.y,
+            MouseScrollUnit::Pixel => ev.y / 20.0,
+        })
+        .sum::<f32>();
+
+    if !ui::ui_is_active(ui_active) {
+        // rotation around the focus, moving the camera in its own plane while panning
+        let right = transform.right().as_vec3();
+        let up = transform.up().as_vec3();
+
+        if input.pressed(Action::DragPan) {
+            if input.pressed(Action::Pan3d) {
+                let pixel = orbit.distance / 1000.0;
+                orbit.offset += (-right * motion.x + up * motion.y) * pixel;
+            } else {
+                orbit.yaw -= motion.x * ROTATE_SPEED;
+                orbit.pitch = (orbit.pitch + motion.y * ROTATE_SPEED).clamp(-1.5, 1.5);
+            }
+        }
+
+        orbit.distance *= (-scroll * 0.1).exp();
+
+        let forward = up.xy().extend(0.0).normalize_or_zero();
+        let mut pan = Vec3::ZERO;
+        for (action, direction) in [
+            (Action::PanUp, forward),
+            (Action::PanDown, -forward),
+            (Action::PanLeft, -right),
+            (Action::PanRight, right),
+        ] {
+            if input.pressed(action) {
+                pan += direction;
+            }
+        }
+        let distance = orbit.distance;
+        orbit.offset += pan * distance * PAN_SPEED * time.delta_seconds();
+    }
+
+    let anchor = if focused.is_empty() {
+        Vec3::ZERO
+    } else {
+        let (weighted, total_mass) = focused
+            .iter()
+            .filter_map(|(trajectory, Mass(mass))| Some((trajectory.front()?, *mass)))
+            .fold((Vec3::ZERO, 0.0), |(weighted, total), (current, mass)| {
+                (
+                    weighted + current_frame.0.pos3_in_frame(current.position3()) * mass,
+                    total + mass,
+                )
+            });
+        if total_mass > 0.0 {
+            weighted / total_mass
+        } else {
+            Vec3::ZERO
+        }
+    };
+
+    *transform = orbit.transform(anchor + orbit.offset);
+
+    // systems range from a few to millions of units across
+    if let Projection::Perspective(perspective) = &mut *projection {
+        perspective.near = orbit.distance * 1e-3;
+        perspective.far = orbit.distance * 1e3;
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn update_body_spheres(
+    bodies: Query<(Entity, &Transform, &Trajectory, &Handle<ColorMaterial>), Without<BodySphere>>,
+    mut spheres: Query<(
+        Entity,
+        &BodySphere,
+        &mut Transform,
+        &Handle<StandardMaterial>,
+    )>,
+    current_frame: Res<CurrentFrame>,
+    colour_materials: Res<Assets<ColorMaterial>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut cmds: Commands,
+) {
+    let mut with_sphere = HashSet::new();
+
+    for (entity, sphere, mut transform, material) in spheres.iter_mut() {
+        let Ok((_, body_transform, trajectory, colour_material)) = bodies.get(sphere.body) else {
+            cmds.entity(entity).despawn();
+            continue;
+        };
+        with_sphere.insert(sphere.body);
+
+        // the in-plane position is already interpolated for the 2D view
+        let z = trajectory.front().map_or(0.0, |current| {
+            current_frame.0.pos3_in_frame(current.position3()).z
+        });
+        transform.translation = body_transform.translation.xy().extend(z);
+        transform.scale = Vec3::splat(body_transform.scale.x);
+
+        let colour = colour_materials
+            .get(colour_material)
+            .map_or(Color::WHITE, |material| material.color);
+        if materials
+            .get(material)
+            .is_some_and(|material| material.base_color != colour)
+        {
+            if let Some(material) = materials.get_mut(material) {
+                material.base_color = colour;
+            }
+        }
+    }
+
+    for (entity, ..) in bodies
+        .iter()
+        .filter(|(entity, ..)| !with_sphere.contains(entity))
+    {
+        cmds.spawn((
+            PbrBundle {
+                // the same size as the circles of the 2D view
+                mesh: meshes.add(Sphere::new(0.5)),
+                material: materials.add(StandardMaterial {
+                    perceptual_roughness: 0.9,
+                    ..default()
+                }),
+                ..default()
+            },
+            BodySphere { body: entity },
+        ));
+    }
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn update_trajectory_lines(
+    sim: Res<SimData>,
+    clock: Res<SimClock>,
+    frame: Res<ReferenceFrame>,
+    bodies: Query<(
+        Entity,
+        &Trajectory,
+        Option<Ref<TrajectoryStyle>>,
+        Ref<TrajectoryVisibility>,
+        &Handle<ColorMaterial>,
+        &Mass,
+    )>,
+    mut lines: Query<(
+        Entity,
+        &mut TrajectoryLine3d,
+        &Handle<Mesh>,
+        &mut Visibility,
+    )>,
+    camera: Query<(&Camera, &OrbitCamera, &Projection)>,
+    colour_materials: Res<Assets<ColorMaterial>>,
+    mut line_materials: ResMut<Assets<LineMaterial3d>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut cache: Local<LineCache>,
+    mut material: Local<Option<Handle<LineMaterial3d>>>,
+    mut cmds: Commands,
+) {
+    let (camera, orbit, projection) = camera.single();
+    let Some(viewport) = camera.logical_viewport_size() else {
+        return;
+    };
+    let fov = match projection {
+        Projection::Perspective(perspective) => perspective.fov,
+        Projection::Orthographic(_) => FRAC_PI_2,
+    };
+    let scale = orbit.distance * 2.0 * (fov / 2.0).tan() / viewport.y;
+
+    let current = LineMaterial3d {
+        scale,
+        pixel_ratio: camera.target_scaling_factor().unwrap_or(1.0),
+    };
+    // shared by all lines, the colour comes from the vertices
+    let material = material
+        .get_or_insert_with(|| line_materials.add(current.clone()))
+        .clone();
+    // only touched when the zoom changes, as every change is sent to the GPU again
+    if line_materials
+        .get(&material)
+        .is_some_and(|material| *material != current)
+    {
+        *line_materials.get_mut(&material).unwrap() = current;
+    }
+
+    // the inspector touches the simulation data every frame, compare the values instead
+    let LineChanges {
+        rescaled, resized, ..
+    } = cache.update(scale, sim.trajectory_len(), None);
+
+    let all = bodies
+        .iter()
+        .map(|(entity, traj, .., mass)| (entity, traj, mass.0))
+        .collect::<Vec<_>>();
+    let mut all_frames = None;
+
+    let mut with_line = HashSet::new();
+
+    for (entity, mut line, mesh, mut visibility) in lines.iter_mut() {
+        let Ok((body, traj, style, vis, colour_material, _)) = bodies.get(line.body) else {
+            cmds.entity(entity).despawn();
+            continue;
+        };
+        with_line.insert(line.body);
+
+        let visible = vis.0 && *frame != ReferenceFrame::Body(body);
+        *visibility = if visible {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+        if !visible {
+            // hidden lines aren't kept up to date
+            line.built = None;
+            continue;
+        }
+
+        let body_colour = colour_materials
+            .get(colour_material)
+            .map_or(Color::WHITE, |material| material.color);
+        let style_changed = style.as_ref().is_some_and(|style| style.is_changed());
+        let style = style.map_or_else(TrajectoryStyle::default, |style| style.clone());
+
+        let steps = LineSteps {
+            kind: LineKind::Prediction,
+            now: clock.steps,
+            len: traj.iter().len() as u64,
+        };
+        let shape = LineShape {
+            tolerance: trajectory::DECIMATION_TOLERANCE * scale,
+            width: style.width,
+            dash: style.dashed.then_some(trajectory::DASH_LENGTH),
+            in_plane: false,
+        };
+        let rebuild = rescaled
+            || frame.is_changed()
+            || style_changed
+            || vis.is_changed()
+            || line.built_colour != Some(body_colour);
+
+        let recoloured = trajectory::update_line(
+            &mut line.built,
+            LineUpdate {
+                history: traj,
+                all: &all,
+                steps,
+                shape,
+                rebuild,
+                recolour: resized,
+            },
+            &frame,
+            &mut all_frames,
+            &mut meshes,
+            mesh,
+            |built| {
+                trajectory::prediction_colours(
+                    built,
+                    &style,
+                    body_colour,
+                    clock.steps,
+                    sim.trajectory_len(),
+                )
+            },
+        );
+        if recoloured {
+            line.built_colour = Some(body_colour);
+        }
+    }
+
+    for (entity, ..) in bodies
+        .iter()
+        .filter(|(entity, ..)| !with_line.contains(entity))
+    {
+        cmds.spawn((
+            MaterialMeshBundle {
+                mesh: meshes.add(trajectory::line_mesh()),
+                material: material.clone(),
+                ..default()
+            },
+            NotShadowCaster,
+            // the mesh grows and moves with the trajectory and the shader widens it
+            NoFrustumCulling,
+            TrajectoryLine3d {
+                body: entity,
+                built_colour: None,
+                built: None,
+            },
+        ));
+    }
+}
+
+pub fn in_2d(view_mode: Res<State<ViewMode>>) -> bool {
+    *view_mode.get() == ViewMode::TwoD
+}
+
+pub struct View3dPlugin;
+
+impl Plugin for View3dPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_state::<ViewMode>()
+            .add_plugins(MaterialPlugin::<LineMaterial3d> {
+                prepass_enabled: false,
+                shadows_enabled: false,
+                ..default()
+            })
+            .insert_resource(AmbientLight {
+                color: Color::WHITE,
+                brightness: 200.0,
+            })
+            // the 2D camera controls don't apply to the orbit camera
+            .configure_sets(PostUpdate, ControlSystemSet.run_if(in_2d))
+            .add_systems(Startup, setup)
+            .add_systems(OnEnter(ViewMode::TwoD), switch_camera)
+            .add_systems(OnEnter(ViewMode::ThreeD), switch_camera)
+            .add_systems(
+                Update,
+                update_trajectory_lines
+                    .in_set(SimSystemSet)
+                    .run_if(in_state(ViewMode::ThreeD)),
+            )
+            .add_systems(
+                PostUpdate,
+                (update_body_spheres, orbit_camera_controller)
+                    .chain()
+                    .after(sim::frame::apply_reference_frame)
+                    .run_if(in_state(AppState::Simulating))
+                    .run_if(in_state(ViewMode::ThreeD)),
+            )
+            .add_systems(
+                OnExit(AppState::Simulating),
+                (
+                    utils::cleanup::<BodySphere>,
+                    utils::cleanup::<TrajectoryLine3d>,
+                ),
+            );
+    }
+}